@@ -1,27 +1,201 @@
 use thiserror::Error;
 
+use crate::parser::Span;
+
 #[derive(Debug, Error)]
 pub enum ParserError {
+    #[error("Syntax error: {0}")]
+    Syntax(String, Span),
     #[error("Syntax error: no name found for function (this should not happen)")]
-    NoFunctionName,
+    NoFunctionName(Span),
     #[error("Syntax error: no equals sing found '='")]
-    NoEquals,
+    NoEquals(Span),
     #[error("Syntax error: too many equals signs")]
-    EqualsCount,
+    EqualsCount(Span),
     #[error("Syntax error: invalid token '{0}'")]
-    InvalidToken(String),
+    InvalidToken(String, Span),
     #[error("Syntax error: invalid operator '{0}'")]
-    InvalidOperator(String),
-    #[error("Syntax error: unknown constant '{0}'")]
-    UnknownConstant(String),
+    InvalidOperator(String, Span),
+    #[error("Syntax error: invalid number '{0}'")]
+    InvalidNumber(String, Span),
+    #[error("Syntax error: function '{0}' is missing its arguments")]
+    MissingArguments(String, Span),
+    #[error("Syntax error: unknown operator '{0}'")]
+    UnknownOperator(String, Span),
+    #[error("Syntax error: unexpected '{0}'")]
+    UnexpectedToken(String, Span),
+    #[error("Syntax error: expected an operand")]
+    ExpectedOperand(Span),
+    #[error("Syntax error: unclosed '|'")]
+    UnclosedBar(Span),
+    #[error("Syntax error: unclosed '('")]
+    UnclosedParen(Span),
+    #[error("Syntax error: unclosed '['")]
+    UnclosedBracket(Span),
+    #[error("Syntax error: unclosed '{{'")]
+    UnclosedBrace(Span),
+    #[error("Syntax error: piecewise expects (value, condition) cases and an optional fallback")]
+    InvalidPiecewise(Span),
+    #[error("Syntax error: '{0}' can't be used as an index variable")]
+    InvalidIndexVariable(String, Span),
+    #[error("Syntax error: '{0}' can't be used as a parameter")]
+    InvalidParameter(String, Span),
+    #[error("Syntax error: can't assign to '{0}'")]
+    InvalidAssignment(String, Span),
+    #[error("Syntax error: '{0}' can't be used as the exponent of a monomial")]
+    InvalidExponent(String, Span),
+    #[error("Syntax error: can't differentiate with respect to '{0}'")]
+    InvalidDerivativeVariable(String, Span),
+    #[error("Syntax error: '{0}' is not the order of a derivative")]
+    InvalidDerivativeOrder(String, Span),
+    #[error("Syntax error: only calls with one argument can take primes, not '{0}'")]
+    InvalidPrime(String, Span),
+    #[error("Syntax error: only user-defined functions can take primes, not '{0}'")]
+    BuiltinPrime(String, Span),
+    #[error("Syntax error: unknown command '{0}'")]
+    UnknownCommand(String, Span),
+}
+
+impl ParserError {
+    /// Location of the offending part of the source.
+    pub fn span(&self) -> Span {
+        match self {
+            ParserError::Syntax(_, span)
+            | ParserError::NoFunctionName(span)
+            | ParserError::NoEquals(span)
+            | ParserError::EqualsCount(span)
+            | ParserError::InvalidToken(_, span)
+            | ParserError::InvalidOperator(_, span)
+            | ParserError::InvalidNumber(_, span)
+            | ParserError::MissingArguments(_, span)
+            | ParserError::UnknownOperator(_, span)
+            | ParserError::UnexpectedToken(_, span)
+            | ParserError::ExpectedOperand(span)
+            | ParserError::UnclosedBar(span)
+            | ParserError::UnclosedParen(span)
+            | ParserError::UnclosedBracket(span)
+            | ParserError::UnclosedBrace(span)
+            | ParserError::InvalidPiecewise(span)
+            | ParserError::InvalidIndexVariable(_, span)
+            | ParserError::InvalidParameter(_, span)
+            | ParserError::InvalidAssignment(_, span)
+            | ParserError::InvalidExponent(_, span)
+            | ParserError::InvalidDerivativeVariable(_, span)
+            | ParserError::InvalidDerivativeOrder(_, span)
+            | ParserError::InvalidPrime(_, span)
+            | ParserError::BuiltinPrime(_, span)
+            | ParserError::UnknownCommand(_, span) => *span,
+        }
+    }
+}
+
+/// Problems with the separators of a [`ParseConfig`](crate::parser::ParseConfig),
+/// which aren't tied to any input.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("'{0}' can't be used as a separator")]
+    InvalidSeparator(char),
+    #[error("'{0}' can't be used for more than one separator")]
+    DuplicateSeparator(char),
 }
 
 #[derive(Debug, Error)]
 pub enum EvaluatorError {
     #[error("Syntax error: can't find function with the name '{0}'")]
-    UnknownFunction(String),
+    UnknownFunction(String, Span),
     #[error("Error while parsing: {0}")]
     ParseFailure(ParserError),
-    #[error("Equality found in evaluator")]
-    EqualityInEval,
+    #[error("Expected a number but found {0}")]
+    ExpectedNumber(String, Span),
+    #[error("Unknown variable '{0}'")]
+    UnknownVariable(String, Span),
+    #[error("Function '{0}' takes {1} argument(s) but {2} were given")]
+    ArgumentCount(String, usize, usize, Span),
+    #[error("Function '{0}' exceeded the maximum recursion depth")]
+    RecursionLimit(String, Span),
+    #[error("Expected a list but found {0}")]
+    ExpectedList(String, Span),
+    #[error("Lists of length {0} and {1} can't be combined element-wise")]
+    LengthMismatch(usize, usize, Span),
+    #[error("Index {0} is out of range for a list of length {1}")]
+    IndexOutOfRange(String, usize, Span),
+    #[error("Invalid range, the bounds must be finite and at most a million apart")]
+    InvalidRange(Span),
+    #[error("Expected a matrix but found {0}")]
+    ExpectedMatrix(String, Span),
+    #[error("Expected a square matrix but found a {0} matrix")]
+    NotSquare(String, Span),
+    #[error("Shapes {0} and {1} don't match")]
+    ShapeMismatch(String, String, Span),
+    #[error("The matrix is singular")]
+    SingularMatrix(Span),
+    #[error("Matrices can only be raised to whole powers")]
+    MatrixPower(Span),
+    #[error("Matrices can't be divided by each other, multiply by inv(B) instead")]
+    MatrixDivision(Span),
+    #[error("The matrix is not symmetric positive definite")]
+    NotPositiveDefinite(Span),
+    #[error("The eigenvalue iteration did not converge")]
+    NoConvergence(Span),
+    #[error("Expected a condition but found {0}")]
+    ExpectedCondition(String, Span),
+    #[error("None of the piecewise conditions hold")]
+    NoMatchingCase(Span),
+    #[error("Invalid bounds, they must be whole numbers at most a million apart or infinite")]
+    InvalidBounds(Span),
+    #[error("The series doesn't converge")]
+    Divergent(Span),
+    #[error("Expected a function but found {0}")]
+    ExpectedFunction(String, Span),
+    #[error("Can't reduce an empty list without an initial value")]
+    EmptyReduce(Span),
+    #[error("Expected a set but found {0}")]
+    ExpectedSet(String, Span),
+    #[error("The lower end of an interval can't be above its upper end")]
+    InvalidInterval(Span),
+    #[error("Derivatives have to be resolved before they can be evaluated")]
+    UnresolvedDerivative(Span),
+}
+
+impl EvaluatorError {
+    /// Location of the offending part of the source.
+    pub fn span(&self) -> Span {
+        match self {
+            EvaluatorError::ParseFailure(err) => err.span(),
+            EvaluatorError::UnknownFunction(_, span)
+            | EvaluatorError::ExpectedNumber(_, span)
+            | EvaluatorError::UnknownVariable(_, span)
+            | EvaluatorError::ArgumentCount(_, _, _, span)
+            | EvaluatorError::RecursionLimit(_, span)
+            | EvaluatorError::ExpectedList(_, span)
+            | EvaluatorError::LengthMismatch(_, _, span)
+            | EvaluatorError::IndexOutOfRange(_, _, span)
+            | EvaluatorError::InvalidRange(span)
+            | EvaluatorError::ExpectedMatrix(_, span)
+            | EvaluatorError::NotSquare(_, span)
+            | EvaluatorError::ShapeMismatch(_, _, span)
+            | EvaluatorError::SingularMatrix(span)
+            | EvaluatorError::MatrixPower(span)
+            | EvaluatorError::MatrixDivision(span)
+            | EvaluatorError::NotPositiveDefinite(span)
+            | EvaluatorError::NoConvergence(span)
+            | EvaluatorError::ExpectedCondition(_, span)
+            | EvaluatorError::NoMatchingCase(span)
+            | EvaluatorError::InvalidBounds(span)
+            | EvaluatorError::Divergent(span)
+            | EvaluatorError::ExpectedFunction(_, span)
+            | EvaluatorError::EmptyReduce(span)
+            | EvaluatorError::ExpectedSet(_, span)
+            | EvaluatorError::InvalidInterval(span)
+            | EvaluatorError::UnresolvedDerivative(span) => *span,
+        }
+    }
+}
+
+/// Finds the source location of an error returned by the parser or the evaluator.
+pub fn error_span(error: &anyhow::Error) -> Option<Span> {
+    if let Some(err) = error.downcast_ref::<ParserError>() {
+        return Some(err.span());
+    }
+    error.downcast_ref::<EvaluatorError>().map(|err| err.span())
 }
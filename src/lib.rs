@@ -1,10 +1,12 @@
 use wasm_bindgen::prelude::*;
 
-mod error;
+use numeric_evaluator::Value;
+
+pub mod error;
 mod math;
 pub mod numeric_evaluator;
 mod optimizer;
-mod parser;
+pub mod parser;
 
 #[cfg(test)]
 mod tests;
@@ -16,3 +18,80 @@ pub fn evaluate(expression: &str) -> Result<f64, String> {
         Err(err) => Err(err.to_string()),
     }
 }
+
+/// Evaluates `expression` to a number, or to a boolean when it is a relation.
+#[wasm_bindgen]
+pub fn evaluate_value(expression: &str) -> Result<JsValue, String> {
+    match numeric_evaluator::evaluate_value(expression) {
+        Ok(Value::Number(val)) => Ok(JsValue::from_f64(val)),
+        Ok(Value::Bool(val)) => Ok(JsValue::from_bool(val)),
+        Ok(val) => Ok(JsValue::from_str(&val.to_string())),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Evaluates the LaTeX emitted by math editors, such as `\frac{1}{2}`.
+#[wasm_bindgen]
+pub fn evaluate_latex(expression: &str) -> Result<f64, String> {
+    match numeric_evaluator::evaluate_latex(expression) {
+        Ok(val) => Ok(val),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Selects the separators of every following call, such as `',', ';'` and `'.'` for
+/// `max(1.000,5; 2)`. The thousands separator is optional.
+#[wasm_bindgen]
+pub fn set_separators(
+    decimal: char,
+    argument: char,
+    thousands: Option<char>,
+) -> Result<(), String> {
+    match parser::ParseConfig::new(decimal, argument, thousands) {
+        Ok(config) => {
+            parser::set_parse_config(config);
+            Ok(())
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Forgets every variable assigned with `name := value`.
+#[wasm_bindgen]
+pub fn clear_variables() {
+    numeric_evaluator::environment().clear();
+}
+
+/// Character range `[start, end]` of the error raised while evaluating `expression`,
+/// or nothing when it evaluates successfully.
+#[wasm_bindgen]
+pub fn locate_error(expression: &str) -> Option<Vec<u32>> {
+    let err = numeric_evaluator::evaluate(expression).err()?;
+    let range = error::error_span(&err)?.char_range(expression);
+    Some(vec![range.start as u32, range.end as u32])
+}
+
+/// Tokens of `expression` for syntax highlighting, as `[kind, start, end]` triples of
+/// character offsets where `kind` is a `TokenKind`.
+#[wasm_bindgen]
+pub fn tokenize(expression: &str) -> Vec<u32> {
+    let mut tokens = Vec::new();
+    for token in parser::tokenize(expression).tokens {
+        let range = token.span.char_range(expression);
+        tokens.extend([token.kind as u32, range.start as u32, range.end as u32]);
+    }
+    tokens
+}
+
+/// Character offsets `[open, close]` of every matching pair of brackets in
+/// `expression`, in the order they are closed.
+#[wasm_bindgen]
+pub fn bracket_pairs(expression: &str) -> Vec<u32> {
+    let mut pairs = Vec::new();
+    for (open, close) in parser::tokenize(expression).brackets {
+        let open = open.char_range(expression).start;
+        let close = close.char_range(expression).start;
+        pairs.extend([open as u32, close as u32]);
+    }
+    pairs
+}
@@ -0,0 +1,238 @@
+use super::Matrix;
+
+/// Sweeps of Jacobi rotations before giving up on orthogonalizing.
+const MAX_SWEEPS: usize = 100;
+
+impl Matrix {
+    /// LU decomposition with partial pivoting of a square matrix. Returns `(L, U, P)`
+    /// with `P * A = L * U`, where `L` has ones on its diagonal.
+    pub fn lu(&self) -> (Matrix, Matrix, Matrix) {
+        let size = self.rows();
+        let mut lower = Matrix::identity(size);
+        let mut upper = self.clone();
+        let mut permutation = Matrix::identity(size);
+        for col in 0..size {
+            let pivot = upper.pivot_row(col, col);
+            if pivot != col {
+                upper.swap_rows(col, pivot);
+                permutation.swap_rows(col, pivot);
+                for i in 0..col {
+                    let value = lower.get(col, i);
+                    lower.set(col, i, lower.get(pivot, i));
+                    lower.set(pivot, i, value);
+                }
+            }
+
+            let scale = upper.get(col, col);
+            if scale == 0.0 {
+                continue;
+            }
+            for row in col + 1..size {
+                let factor = upper.get(row, col) / scale;
+                lower.set(row, col, factor);
+                for i in col..size {
+                    upper.set(row, i, upper.get(row, i) - factor * upper.get(col, i));
+                }
+                upper.set(row, col, 0.0);
+            }
+        }
+        (lower, upper, permutation)
+    }
+
+    /// QR decomposition by Householder reflections. Returns `(Q, R)` with an
+    /// orthogonal `Q` and an upper triangular `R` whose diagonal isn't negative.
+    pub fn qr(&self) -> (Matrix, Matrix) {
+        let (rows, cols) = (self.rows(), self.cols());
+        let mut q = Matrix::identity(rows);
+        let mut r = self.clone();
+        for k in 0..cols.min(rows.saturating_sub(1)) {
+            let norm = (k..rows).map(|i| r.get(i, k).powi(2)).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if r.get(k, k) > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k..rows).map(|i| r.get(i, k)).collect();
+            v[0] -= alpha;
+            let length = v.iter().map(|x| x * x).sum::<f64>();
+            if length == 0.0 {
+                continue;
+            }
+
+            // R = H * R and Q = Q * H with the reflection H = I - 2vvᵀ/vᵀv
+            for col in 0..cols {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * r.get(k + i, col))
+                    .sum();
+                for (i, x) in v.iter().enumerate() {
+                    r.set(k + i, col, r.get(k + i, col) - 2.0 * dot / length * x);
+                }
+            }
+            for row in 0..rows {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * q.get(row, k + i))
+                    .sum();
+                for (i, x) in v.iter().enumerate() {
+                    q.set(row, k + i, q.get(row, k + i) - 2.0 * dot / length * x);
+                }
+            }
+        }
+
+        for i in 0..rows.min(cols) {
+            if r.get(i, i) < 0.0 {
+                for col in 0..cols {
+                    r.set(i, col, -r.get(i, col));
+                }
+                for row in 0..rows {
+                    q.set(row, i, -q.get(row, i));
+                }
+            }
+            for row in i + 1..rows {
+                r.set(row, i, 0.0);
+            }
+        }
+        (q, r)
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        let tolerance = self.tolerance();
+        self.is_square()
+            && (0..self.rows()).all(|row| {
+                (0..row).all(|col| (self.get(row, col) - self.get(col, row)).abs() <= tolerance)
+            })
+    }
+
+    /// Lower triangular `L` with `A = L * Lᵀ`. Only symmetric positive definite
+    /// matrices have one.
+    pub fn cholesky(&self) -> Option<Matrix> {
+        if !self.is_symmetric() {
+            return None;
+        }
+        let size = self.rows();
+        let tolerance = self.tolerance();
+        let mut lower = Matrix::new(size, size, vec![0.0; size * size]);
+        for j in 0..size {
+            let diagonal = self.get(j, j) - (0..j).map(|k| lower.get(j, k).powi(2)).sum::<f64>();
+            if diagonal <= tolerance {
+                return None;
+            }
+            let diagonal = diagonal.sqrt();
+            lower.set(j, j, diagonal);
+            for i in j + 1..size {
+                let sum: f64 = (0..j).map(|k| lower.get(i, k) * lower.get(j, k)).sum();
+                lower.set(i, j, (self.get(i, j) - sum) / diagonal);
+            }
+        }
+        Some(lower)
+    }
+
+    /// Thin singular value decomposition by one-sided Jacobi rotations. Returns
+    /// `(U, S, V)` with `A = U * diag(S) * Vᵀ` and the singular values `S` in
+    /// descending order.
+    pub fn svd(&self) -> (Matrix, Vec<f64>, Matrix) {
+        if self.rows() < self.cols() {
+            let (u, singular_values, v) = self.transpose().svd();
+            return (v, singular_values, u);
+        }
+
+        let (rows, cols) = (self.rows(), self.cols());
+        let mut u = self.clone();
+        let mut v = Matrix::identity(cols);
+        for _ in 0..MAX_SWEEPS {
+            let mut rotated = false;
+            for i in 0..cols {
+                for j in i + 1..cols {
+                    let alpha: f64 = (0..rows).map(|k| u.get(k, i).powi(2)).sum();
+                    let beta: f64 = (0..rows).map(|k| u.get(k, j).powi(2)).sum();
+                    let gamma: f64 = (0..rows).map(|k| u.get(k, i) * u.get(k, j)).sum();
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                    let c = 1.0 / (1.0 + t * t).sqrt();
+                    let s = c * t;
+                    rotate_columns(&mut u, i, j, c, s);
+                    rotate_columns(&mut v, i, j, c, s);
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+
+        let norms: Vec<f64> = (0..cols)
+            .map(|col| (0..rows).map(|k| u.get(k, col).powi(2)).sum::<f64>().sqrt())
+            .collect();
+        let mut order: Vec<usize> = (0..cols).collect();
+        order.sort_by(|a, b| norms[*b].total_cmp(&norms[*a]));
+        let tolerance = f64::EPSILON * norms[order[0]].max(f64::MIN_POSITIVE) * rows as f64;
+
+        let mut left = Matrix::new(rows, cols, vec![0.0; rows * cols]);
+        let mut right = Matrix::new(cols, cols, vec![0.0; cols * cols]);
+        let mut singular_values = Vec::with_capacity(cols);
+        for (col, &source) in order.iter().enumerate() {
+            // Makes the largest component of every right singular vector positive
+            let largest = (0..cols)
+                .max_by(|a, b| v.get(*a, source).abs().total_cmp(&v.get(*b, source).abs()))
+                .unwrap();
+            let sign = v.get(largest, source).signum();
+            for row in 0..cols {
+                right.set(row, col, sign * v.get(row, source));
+            }
+
+            let norm = norms[source];
+            if norm > tolerance {
+                for row in 0..rows {
+                    left.set(row, col, sign * u.get(row, source) / norm);
+                }
+                singular_values.push(norm);
+            } else {
+                complete_basis(&mut left, col);
+                singular_values.push(0.0);
+            }
+        }
+        (left, singular_values, right)
+    }
+}
+
+fn rotate_columns(matrix: &mut Matrix, i: usize, j: usize, c: f64, s: f64) {
+    for k in 0..matrix.rows() {
+        let (a, b) = (matrix.get(k, i), matrix.get(k, j));
+        matrix.set(k, i, c * a - s * b);
+        matrix.set(k, j, s * a + c * b);
+    }
+}
+
+/// Fills column `col` with a unit vector orthogonal to the columns before it, by
+/// orthogonalizing the standard basis vector that keeps the most of its length.
+fn complete_basis(matrix: &mut Matrix, col: usize) {
+    let rows = matrix.rows();
+    let mut best = vec![0.0; rows];
+    let mut best_norm = 0.0;
+    for basis in 0..rows {
+        let mut candidate = vec![0.0; rows];
+        candidate[basis] = 1.0;
+        for previous in 0..col {
+            let dot: f64 = (0..rows)
+                .map(|k| candidate[k] * matrix.get(k, previous))
+                .sum();
+            for (k, value) in candidate.iter_mut().enumerate() {
+                *value -= dot * matrix.get(k, previous);
+            }
+        }
+        let norm = candidate.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > best_norm {
+            best = candidate;
+            best_norm = norm;
+        }
+    }
+    for (row, value) in best.into_iter().enumerate() {
+        matrix.set(row, col, value / best_norm);
+    }
+}
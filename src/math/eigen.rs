@@ -0,0 +1,404 @@
+use super::{Complex, Matrix};
+
+/// Iterations of the QR algorithm per eigenvalue before giving up.
+const MAX_ITERATIONS: usize = 100;
+/// Sweeps of Jacobi rotations before giving up on diagonalizing.
+const MAX_SWEEPS: usize = 100;
+
+type ComplexMatrix = Vec<Vec<Complex>>;
+
+impl Matrix {
+    /// Eigenvalues of a square matrix sorted by descending real part, with complex
+    /// conjugate pairs for rotations and the like. Nothing is returned when the
+    /// iteration doesn't converge.
+    pub fn eigenvalues(&self) -> Option<Vec<Complex>> {
+        if self.is_symmetric() {
+            let (values, _) = self.jacobi()?;
+            let mut values: Vec<Complex> = values.into_iter().map(Complex::real).collect();
+            sort_eigenvalues(&mut values);
+            return Some(values);
+        }
+
+        let mut values = hessenberg_qr(self.hessenberg())?;
+        // Imaginary parts this small are rounding noise of real eigenvalues
+        let scale = self.tolerance() * 100.0;
+        for value in values.iter_mut() {
+            if value.im.abs() <= scale {
+                value.im = 0.0;
+            }
+        }
+        // The eigenvalues of a real matrix come in exact conjugate pairs
+        let upper: Vec<Complex> = values.iter().filter(|v| v.im > 0.0).copied().collect();
+        let lower = values.iter().filter(|v| v.im < 0.0).count();
+        if upper.len() == lower {
+            values.retain(|value| value.im == 0.0);
+            for value in upper {
+                values.push(value);
+                values.push(value.conj());
+            }
+        }
+        sort_eigenvalues(&mut values);
+        Some(values)
+    }
+
+    /// Eigenvalues together with eigenvectors of unit length, ordered like
+    /// [`eigenvalues`](Matrix::eigenvalues). The largest component of every vector
+    /// is real and positive.
+    pub fn eigen(&self) -> Option<Vec<(Complex, Vec<Complex>)>> {
+        if self.is_symmetric() {
+            let (values, vectors) = self.jacobi()?;
+            let mut pairs: Vec<(Complex, Vec<Complex>)> = values
+                .into_iter()
+                .enumerate()
+                .map(|(col, value)| {
+                    let vector = (0..self.rows())
+                        .map(|row| Complex::real(vectors.get(row, col)))
+                        .collect();
+                    (Complex::real(value), normalize(vector))
+                })
+                .collect();
+            pairs.sort_by(|a, b| b.0.re.total_cmp(&a.0.re));
+            return Some(pairs);
+        }
+
+        let values = self.eigenvalues()?;
+        Some(
+            values
+                .into_iter()
+                .map(|value| {
+                    let mut vector = self.inverse_iteration(value);
+                    if value.im == 0.0 {
+                        vector = vector.into_iter().map(|x| Complex::real(x.re)).collect();
+                    }
+                    (value, vector)
+                })
+                .collect(),
+        )
+    }
+
+    /// Eigenvalues and eigenvectors of a symmetric matrix by cyclic Jacobi rotations.
+    /// The eigenvectors are the columns of the returned matrix.
+    fn jacobi(&self) -> Option<(Vec<f64>, Matrix)> {
+        let size = self.rows();
+        let mut a = self.clone();
+        let mut vectors = Matrix::identity(size);
+        let total: f64 = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .map(|(row, col)| self.get(row, col).powi(2))
+            .sum();
+
+        let mut converged = false;
+        for _ in 0..MAX_SWEEPS {
+            let off: f64 = (0..size)
+                .flat_map(|row| (0..row).map(move |col| (row, col)))
+                .map(|(row, col)| a.get(row, col).powi(2))
+                .sum();
+            if off <= f64::EPSILON.powi(2) * total {
+                converged = true;
+                break;
+            }
+
+            for p in 0..size {
+                for q in p + 1..size {
+                    if a.get(p, q) == 0.0 {
+                        continue;
+                    }
+                    let theta = (a.get(q, q) - a.get(p, p)) / (2.0 * a.get(p, q));
+                    let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                    let c = 1.0 / (t * t + 1.0).sqrt();
+                    let s = t * c;
+                    // A = Jᵀ * A * J and V = V * J
+                    for k in 0..size {
+                        let (kp, kq) = (a.get(k, p), a.get(k, q));
+                        a.set(k, p, c * kp - s * kq);
+                        a.set(k, q, s * kp + c * kq);
+                    }
+                    for k in 0..size {
+                        let (pk, qk) = (a.get(p, k), a.get(q, k));
+                        a.set(p, k, c * pk - s * qk);
+                        a.set(q, k, s * pk + c * qk);
+                    }
+                    for k in 0..size {
+                        let (kp, kq) = (vectors.get(k, p), vectors.get(k, q));
+                        vectors.set(k, p, c * kp - s * kq);
+                        vectors.set(k, q, s * kp + c * kq);
+                    }
+                }
+            }
+        }
+
+        match converged {
+            true => Some(((0..size).map(|i| a.get(i, i)).collect(), vectors)),
+            false => None,
+        }
+    }
+
+    /// Upper Hessenberg form by Householder reflections, which has the same
+    /// eigenvalues.
+    fn hessenberg(&self) -> ComplexMatrix {
+        let size = self.rows();
+        let mut h = self.clone();
+        for k in 0..size.saturating_sub(2) {
+            let norm = (k + 1..size)
+                .map(|i| h.get(i, k).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if h.get(k + 1, k) > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = (k + 1..size).map(|i| h.get(i, k)).collect();
+            v[0] -= alpha;
+            let length = v.iter().map(|x| x * x).sum::<f64>();
+            if length == 0.0 {
+                continue;
+            }
+
+            // H = P * H * P with the reflection P = I - 2vvᵀ/vᵀv
+            for col in 0..size {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * h.get(k + 1 + i, col))
+                    .sum();
+                for (i, x) in v.iter().enumerate() {
+                    h.set(
+                        k + 1 + i,
+                        col,
+                        h.get(k + 1 + i, col) - 2.0 * dot / length * x,
+                    );
+                }
+            }
+            for row in 0..size {
+                let dot: f64 = v
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * h.get(row, k + 1 + i))
+                    .sum();
+                for (i, x) in v.iter().enumerate() {
+                    h.set(
+                        row,
+                        k + 1 + i,
+                        h.get(row, k + 1 + i) - 2.0 * dot / length * x,
+                    );
+                }
+            }
+        }
+
+        (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|col| Complex::real(h.get(row, col)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Eigenvector for `value` by inverse iteration with `A - value * I`.
+    fn inverse_iteration(&self, value: Complex) -> Vec<Complex> {
+        let size = self.rows();
+        let mut shifted: ComplexMatrix = (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|col| match row == col {
+                        true => Complex::real(self.get(row, col)) - value,
+                        false => Complex::real(self.get(row, col)),
+                    })
+                    .collect()
+            })
+            .collect();
+        let largest = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .fold(f64::MIN_POSITIVE, |max, (row, col)| {
+                max.max(self.get(row, col).abs())
+            });
+        let permutation = lu_in_place(&mut shifted, f64::EPSILON * largest);
+
+        // An uneven start keeps it from being orthogonal to the eigenvector
+        let mut vector: Vec<Complex> = (0..size)
+            .map(|i| Complex::real(1.0 / (i + 1) as f64))
+            .collect();
+        for _ in 0..3 {
+            vector = lu_solve(&shifted, &permutation, &vector);
+            let largest = largest_component(&vector);
+            vector = vector.into_iter().map(|x| x / largest).collect();
+        }
+        normalize(vector)
+    }
+}
+
+fn sort_eigenvalues(values: &mut [Complex]) {
+    values.sort_by(|a, b| b.re.total_cmp(&a.re).then(b.im.total_cmp(&a.im)));
+}
+
+fn largest_component(vector: &[Complex]) -> Complex {
+    *vector
+        .iter()
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap()
+}
+
+/// Scales `vector` to unit length with its largest component real and positive.
+/// Components that are only rounding noise become zero.
+fn normalize(vector: Vec<Complex>) -> Vec<Complex> {
+    let largest = largest_component(&vector);
+    let phase = largest.conj().scale(1.0 / largest.abs());
+    let length = vector.iter().map(|x| x.abs().powi(2)).sum::<f64>().sqrt();
+    let clean = |x: f64| match x.abs() < 1e-14 {
+        true => 0.0,
+        false => x,
+    };
+    vector
+        .into_iter()
+        .map(|x| {
+            let x = (x * phase).scale(1.0 / length);
+            Complex::new(clean(x.re), clean(x.im))
+        })
+        .collect()
+}
+
+/// Eigenvalues of a Hessenberg matrix by the QR algorithm with Wilkinson shifts,
+/// deflating one eigenvalue at a time from the bottom.
+fn hessenberg_qr(mut h: ComplexMatrix) -> Option<Vec<Complex>> {
+    let mut values = Vec::with_capacity(h.len());
+    let mut end = h.len();
+    let mut iterations = 0;
+    while end > 0 {
+        // Start of the unreduced block ending at `end`
+        let mut start = end - 1;
+        while start > 0 {
+            let scale = h[start][start].abs() + h[start - 1][start - 1].abs();
+            if h[start][start - 1].abs() <= f64::EPSILON * scale {
+                h[start][start - 1] = Complex::real(0.0);
+                break;
+            }
+            start -= 1;
+        }
+        if start == end - 1 {
+            values.push(h[end - 1][end - 1]);
+            end -= 1;
+            iterations = 0;
+            continue;
+        }
+        // A 2×2 block is solved directly, which is exact for small integer matrices
+        if start == end - 2 {
+            let (mean, root) = block_eigenvalues(&h, end);
+            values.push(mean + root);
+            values.push(mean - root);
+            end -= 2;
+            iterations = 0;
+            continue;
+        }
+
+        iterations += 1;
+        if iterations > MAX_ITERATIONS {
+            return None;
+        }
+        let shift = match iterations % 10 {
+            // Exceptional shifts get the iteration out of cycles
+            0 => h[end - 1][end - 1] + Complex::real(h[end - 1][end - 2].abs()),
+            _ => wilkinson_shift(&h, end),
+        };
+        qr_step(&mut h, start, end, shift);
+    }
+    Some(values)
+}
+
+/// The eigenvalues of the 2×2 block ending at `end` are `mean ± root`.
+fn block_eigenvalues(h: &ComplexMatrix, end: usize) -> (Complex, Complex) {
+    let (a, b) = (h[end - 2][end - 2], h[end - 2][end - 1]);
+    let (c, d) = (h[end - 1][end - 2], h[end - 1][end - 1]);
+    let half = (a - d).scale(0.5);
+    ((a + d).scale(0.5), (half * half + b * c).sqrt())
+}
+
+/// Eigenvalue of the trailing 2×2 block closest to its last diagonal entry.
+fn wilkinson_shift(h: &ComplexMatrix, end: usize) -> Complex {
+    let d = h[end - 1][end - 1];
+    let (mean, root) = block_eigenvalues(h, end);
+    let first = mean + root;
+    let second = mean - root;
+    match (first - d).abs() <= (second - d).abs() {
+        true => first,
+        false => second,
+    }
+}
+
+/// One shifted QR step `H - μI = QR, H = RQ + μI` on the rows and columns from
+/// `start` to `end`, using Givens rotations.
+#[allow(clippy::needless_range_loop)]
+fn qr_step(h: &mut ComplexMatrix, start: usize, end: usize, shift: Complex) {
+    for k in start..end {
+        h[k][k] = h[k][k] - shift;
+    }
+
+    let mut rotations = Vec::with_capacity(end - start);
+    for k in start..end - 1 {
+        let (a, b) = (h[k][k], h[k + 1][k]);
+        let norm = (a.abs().powi(2) + b.abs().powi(2)).sqrt();
+        let (c, s) = match norm == 0.0 {
+            true => (Complex::real(1.0), Complex::real(0.0)),
+            false => (a.scale(1.0 / norm), b.scale(1.0 / norm)),
+        };
+        for col in k..end {
+            let (x, y) = (h[k][col], h[k + 1][col]);
+            h[k][col] = c.conj() * x + s.conj() * y;
+            h[k + 1][col] = c * y - s * x;
+        }
+        rotations.push((k, c, s));
+    }
+    for (k, c, s) in rotations {
+        for row in start..(k + 2).min(end) {
+            let (x, y) = (h[row][k], h[row][k + 1]);
+            h[row][k] = c * x + s * y;
+            h[row][k + 1] = c.conj() * y - s.conj() * x;
+        }
+    }
+
+    for k in start..end {
+        h[k][k] = h[k][k] + shift;
+    }
+}
+
+/// LU decomposition with partial pivoting in place. Pivots smaller than `tolerance`
+/// are replaced by it, which keeps inverse iteration going with singular matrices.
+#[allow(clippy::needless_range_loop)]
+fn lu_in_place(matrix: &mut ComplexMatrix, tolerance: f64) -> Vec<usize> {
+    let size = matrix.len();
+    let mut permutation: Vec<usize> = (0..size).collect();
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap();
+        matrix.swap(col, pivot);
+        permutation.swap(col, pivot);
+        if matrix[col][col].abs() < tolerance {
+            matrix[col][col] = Complex::real(tolerance);
+        }
+        for row in col + 1..size {
+            let factor = matrix[row][col] / matrix[col][col];
+            matrix[row][col] = factor;
+            for i in col + 1..size {
+                matrix[row][i] = matrix[row][i] - factor * matrix[col][i];
+            }
+        }
+    }
+    permutation
+}
+
+fn lu_solve(lu: &ComplexMatrix, permutation: &[usize], rhs: &[Complex]) -> Vec<Complex> {
+    let size = lu.len();
+    let mut x: Vec<Complex> = permutation.iter().map(|&i| rhs[i]).collect();
+    for row in 0..size {
+        for col in 0..row {
+            x[row] = x[row] - lu[row][col] * x[col];
+        }
+    }
+    for row in (0..size).rev() {
+        for col in row + 1..size {
+            x[row] = x[row] - lu[row][col] * x[col];
+        }
+        x[row] = x[row] / lu[row][row];
+    }
+    x
+}
@@ -0,0 +1,49 @@
+use phf::phf_set;
+
+pub static FUNCTIONS_DATABASE: phf::Set<&'static str> = phf_set! {
+    "cos",
+    "sin",
+    "tan",
+    "ln",
+    "log",
+    "exp",
+    "floor",
+    "ceil",
+    "round",
+    "trunc",
+    "fract",
+    "sqrt",
+    "cbrt",
+    "root",
+    "abs",
+    "norm",
+    "gamma",
+    "pow",
+    "min",
+    "max",
+    "range",
+    "len",
+    "sum",
+    "mean",
+    "prod",
+    "det",
+    "inv",
+    "transpose",
+    "trace",
+    "rank",
+    "rref",
+    "solve",
+    "eig",
+    "eigvals",
+    "lu",
+    "qr",
+    "cholesky",
+    "svd",
+    "if",
+    "piecewise",
+    "map",
+    "filter",
+    "reduce",
+    "apply",
+    "diff",
+};
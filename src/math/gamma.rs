@@ -0,0 +1,66 @@
+use std::f64::consts::PI;
+
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Gamma function using the Lanczos approximation. Non-positive integers are poles
+/// and evaluate to NaN.
+pub fn gamma(x: f64) -> f64 {
+    if x <= 0.0 && x.fract() == 0.0 {
+        return f64::NAN;
+    }
+
+    // Small positive integers are computed exactly
+    if x > 0.0 && x <= 171.0 && x.fract() == 0.0 {
+        return (1..x as u64).fold(1.0, |acc, n| acc * n as f64);
+    }
+
+    // Reflection formula
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + LANCZOS_G + 0.5;
+    let sum = LANCZOS_COEFFICIENTS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS_COEFFICIENTS[0], |acc, (i, c)| {
+            acc + c / (x + i as f64)
+        });
+
+    (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * sum
+}
+
+pub fn factorial(x: f64) -> f64 {
+    gamma(x + 1.0)
+}
+
+/// Double factorial `x!!`. Non-integers use the analytic continuation
+/// `2^(x/2) * (2/pi)^((1 - cos(pi*x))/4) * gamma(x/2 + 1)`.
+pub fn double_factorial(x: f64) -> f64 {
+    if x.fract() == 0.0 && x >= -1.0 {
+        let mut out = 1f64;
+        let mut n = x;
+        // Large inputs overflow within a few hundred steps, long before `n - 2.0`
+        // stops changing `n`
+        while n > 1.0 && out.is_finite() {
+            out *= n;
+            n -= 2.0;
+        }
+        return out;
+    }
+
+    2f64.powf(x / 2.0) * (2.0 / PI).powf((1.0 - (PI * x).cos()) / 4.0) * gamma(x / 2.0 + 1.0)
+}
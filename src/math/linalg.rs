@@ -0,0 +1,328 @@
+use std::fmt;
+
+/// Entries smaller than this, relative to the largest entry of their row or matrix, are
+/// treated as zero.
+const EPSILON: f64 = 1e-10;
+
+/// Dense matrix of real numbers stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn new(rows: usize, cols: usize, data: Vec<f64>) -> Matrix {
+        assert_eq!(
+            rows * cols,
+            data.len(),
+            "matrix data doesn't match its shape"
+        );
+        Matrix { rows, cols, data }
+    }
+
+    /// Builds a matrix out of rows of equal length.
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Matrix {
+        let cols = rows.first().map_or(0, |row| row.len());
+        Matrix::new(rows.len(), cols, rows.concat())
+    }
+
+    pub fn identity(size: usize) -> Matrix {
+        let mut matrix = Matrix::new(size, size, vec![0.0; size * size]);
+        for i in 0..size {
+            matrix.set(i, i, 1.0);
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Shape for error messages, such as `2×3`.
+    pub fn shape(&self) -> String {
+        format!("{}×{}", self.rows, self.cols)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: f64) {
+        self.data[row * self.cols + col] = value;
+    }
+
+    pub fn row(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<f64>> {
+        match self.cols {
+            0 => vec![Vec::new(); self.rows],
+            cols => self.data.chunks(cols).map(|row| row.to_vec()).collect(),
+        }
+    }
+
+    pub fn map(&self, function: impl Fn(f64) -> f64) -> Matrix {
+        Matrix::new(
+            self.rows,
+            self.cols,
+            self.data.iter().map(|value| function(*value)).collect(),
+        )
+    }
+
+    /// Combines the entries of two matrices of the same shape.
+    pub fn zip(&self, other: &Matrix, function: impl Fn(f64, f64) -> f64) -> Option<Matrix> {
+        if self.rows != other.rows || self.cols != other.cols {
+            return None;
+        }
+        Some(Matrix::new(
+            self.rows,
+            self.cols,
+            self.data
+                .iter()
+                .zip(&other.data)
+                .map(|(lhs, rhs)| function(*lhs, *rhs))
+                .collect(),
+        ))
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let mut transposed = Matrix::new(self.cols, self.rows, vec![0.0; self.data.len()]);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                transposed.set(col, row, self.get(row, col));
+            }
+        }
+        transposed
+    }
+
+    /// Matrix product, or nothing when the columns of `self` don't match the rows of
+    /// `other`.
+    pub fn mul(&self, other: &Matrix) -> Option<Matrix> {
+        if self.cols != other.rows {
+            return None;
+        }
+        let mut product = Matrix::new(self.rows, other.cols, vec![0.0; self.rows * other.cols]);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let value = (0..self.cols)
+                    .map(|i| self.get(row, i) * other.get(i, col))
+                    .sum();
+                product.set(row, col, value);
+            }
+        }
+        Some(product)
+    }
+
+    /// Raises a square matrix to a whole power by repeated squaring. Negative powers
+    /// are powers of the inverse, which doesn't exist for singular matrices.
+    pub fn pow(&self, exponent: i32) -> Option<Matrix> {
+        let mut base = match exponent < 0 {
+            true => self.inverse()?,
+            false => self.clone(),
+        };
+        let mut exponent = exponent.unsigned_abs();
+        let mut result = Matrix::identity(self.rows);
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.mul(&base)?;
+            }
+            base = base.mul(&base)?;
+            exponent /= 2;
+        }
+        Some(result)
+    }
+
+    pub fn trace(&self) -> f64 {
+        (0..self.rows.min(self.cols)).map(|i| self.get(i, i)).sum()
+    }
+
+    /// Threshold below which entries count as zero, scaled by the largest entry.
+    pub(super) fn tolerance(&self) -> f64 {
+        EPSILON
+            * self
+                .data
+                .iter()
+                .fold(0.0, |max: f64, value| max.max(value.abs()))
+    }
+
+    pub(super) fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.data.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+
+    /// Index of the row at or below `start` with the largest entry in `col`.
+    pub(super) fn pivot_row(&self, start: usize, col: usize) -> usize {
+        (start..self.rows)
+            .max_by(|&a, &b| self.get(a, col).abs().total_cmp(&self.get(b, col).abs()))
+            .unwrap()
+    }
+
+    /// Largest entry of every row among its first `cols` columns, which the other
+    /// entries of the row are measured against.
+    fn row_scales(&self, cols: usize) -> Vec<f64> {
+        (0..self.rows)
+            .map(|row| {
+                self.row(row)[..cols]
+                    .iter()
+                    .fold(0.0, |max: f64, value| max.max(value.abs()))
+            })
+            .collect()
+    }
+
+    /// Index of the row at or below `start` with the largest entry in `col` relative
+    /// to the scale of its row, for scaled partial pivoting.
+    fn scaled_pivot_row(&self, start: usize, col: usize, scales: &[f64]) -> usize {
+        let relative = |row: usize| match scales[row] > 0.0 {
+            true => self.get(row, col).abs() / scales[row],
+            false => 0.0,
+        };
+        (start..self.rows)
+            .max_by(|&a, &b| relative(a).total_cmp(&relative(b)))
+            .unwrap()
+    }
+
+    /// Reduced row echelon form by Gauss-Jordan elimination with scaled partial
+    /// pivoting, together with the rank.
+    pub fn rref(&self) -> (Matrix, usize) {
+        self.reduce(self.cols)
+    }
+
+    /// Gauss-Jordan elimination over the first `pivot_cols` columns. Entries count as
+    /// zero when they are tiny next to the scale of their row, so a matrix with small
+    /// but independent rows keeps its full rank. The remaining columns are carried
+    /// along without deciding anything.
+    fn reduce(&self, pivot_cols: usize) -> (Matrix, usize) {
+        let mut matrix = self.clone();
+        let mut scales = self.row_scales(pivot_cols);
+        let mut rank = 0;
+        for col in 0..pivot_cols {
+            if rank == self.rows {
+                break;
+            }
+            let pivot = matrix.scaled_pivot_row(rank, col, &scales);
+            if matrix.get(pivot, col).abs() <= EPSILON * scales[pivot] {
+                continue;
+            }
+            matrix.swap_rows(rank, pivot);
+            scales.swap(rank, pivot);
+
+            let scale = matrix.get(rank, col);
+            for i in 0..self.cols {
+                matrix.set(rank, i, matrix.get(rank, i) / scale);
+            }
+            scales[rank] /= scale.abs();
+            for row in 0..self.rows {
+                let factor = matrix.get(row, col);
+                if row == rank || factor == 0.0 {
+                    continue;
+                }
+                for i in 0..self.cols {
+                    matrix.set(row, i, matrix.get(row, i) - factor * matrix.get(rank, i));
+                }
+                // Subtracting a multiple of the pivot row brings its size along
+                scales[row] = scales[row].max(factor.abs() * scales[rank]);
+            }
+            rank += 1;
+        }
+
+        // Leftovers of the elimination would show up as tiny non-zero entries
+        for (row, scale) in scales.iter().enumerate() {
+            for col in 0..pivot_cols {
+                if matrix.get(row, col).abs() <= EPSILON * scale {
+                    matrix.set(row, col, 0.0);
+                }
+            }
+        }
+        (matrix, rank)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rref().1
+    }
+
+    /// Determinant of a square matrix by Gaussian elimination. Singular matrices give
+    /// exactly zero rather than rounding noise.
+    pub fn det(&self) -> f64 {
+        let mut matrix = self.clone();
+        let mut scales = self.row_scales(self.cols);
+        let mut det = 1.0;
+        for col in 0..self.cols {
+            let pivot = matrix.scaled_pivot_row(col, col, &scales);
+            if matrix.get(pivot, col).abs() <= EPSILON * scales[pivot] {
+                return 0.0;
+            }
+            if pivot != col {
+                matrix.swap_rows(col, pivot);
+                scales.swap(col, pivot);
+                det = -det;
+            }
+            let scale = matrix.get(col, col);
+            det *= scale;
+            for row in col + 1..self.rows {
+                let factor = matrix.get(row, col) / scale;
+                for i in col..self.cols {
+                    matrix.set(row, i, matrix.get(row, i) - factor * matrix.get(col, i));
+                }
+                scales[row] = scales[row].max(factor.abs() * scales[col]);
+            }
+        }
+        det
+    }
+
+    /// Solves `self * X = rhs` for a square `self`. Nothing is returned when `self`
+    /// is singular.
+    pub fn solve(&self, rhs: &Matrix) -> Option<Matrix> {
+        let size = self.rows;
+        let mut data = Vec::with_capacity(size * (size + rhs.cols));
+        for row in 0..size {
+            data.extend_from_slice(self.row(row));
+            data.extend_from_slice(rhs.row(row));
+        }
+        let augmented = Matrix::new(size, size + rhs.cols, data);
+
+        // The left block reduces to the identity exactly when `self` is invertible.
+        // Only its entries decide what counts as zero, however large or small the
+        // right-hand side is.
+        let (reduced, _) = augmented.reduce(size);
+        if (0..size).any(|i| reduced.get(i, i) != 1.0) {
+            return None;
+        }
+        let mut data = Vec::with_capacity(size * rhs.cols);
+        for row in 0..size {
+            data.extend_from_slice(&reduced.row(row)[size..]);
+        }
+        Some(Matrix::new(size, rhs.cols, data))
+    }
+
+    pub fn inverse(&self) -> Option<Matrix> {
+        self.solve(&Matrix::identity(self.rows))
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = (0..self.rows)
+            .map(|row| {
+                let values: Vec<String> = self
+                    .row(row)
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect();
+                format!("[{}]", values.join(", "))
+            })
+            .collect();
+        write!(f, "[{}]", rows.join(", "))
+    }
+}
@@ -1,8 +1,26 @@
 mod round;
 mod angle;
+mod complex;
 mod constants;
+mod decomposition;
+mod eigen;
+mod functions;
+mod gamma;
+mod linalg;
+mod rational;
+mod root;
+mod series;
+mod set;
 
-pub use round::round;
+pub use round::{nearly_equal, round};
 pub use angle::deg_to_rad;
+pub use complex::Complex;
+pub use gamma::{double_factorial, factorial, gamma};
+pub use linalg::Matrix;
+pub use rational::Rational;
+pub use root::root;
+pub use series::{extrapolate_limit, step_count};
+pub use set::{Interval, Set};
 
 pub use constants::CONSTANTS_DATABASE;
+pub use functions::FUNCTIONS_DATABASE;
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::ops::Neg;
+
+/// Exact fraction, used for the exponents of monomials such as `X^(1/2)`. Always in
+/// lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+/// Largest denominator [`Rational::approximate`] looks for.
+const MAX_DENOMINATOR: i64 = 1_000_000;
+
+impl Rational {
+    pub const ZERO: Rational = Rational::integer(0);
+    pub const ONE: Rational = Rational::integer(1);
+
+    /// The fraction `numerator / denominator`, or `None` when the denominator is zero.
+    pub fn new(numerator: i64, denominator: i64) -> Option<Rational> {
+        Rational::reduce(numerator as i128, denominator as i128)
+    }
+
+    pub const fn integer(value: i64) -> Rational {
+        Rational {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    /// The fraction with the smallest denominator that matches `value` up to rounding
+    /// error, so `0.5` is `1/2` and `0.(3)` is `1/3`. `None` for values such as `π`
+    /// that are no fraction with a reasonably small denominator.
+    pub fn approximate(value: f64) -> Option<Rational> {
+        if !value.is_finite() || value.abs() >= i64::MAX as f64 {
+            return None;
+        }
+
+        // Convergents of the continued fraction of `value`
+        let (mut h, mut h_previous) = (value.floor() as i128, 1i128);
+        let (mut k, mut k_previous) = (1i128, 0i128);
+        let mut rest = value - value.floor();
+        loop {
+            let close = (value - h as f64 / k as f64).abs() <= 1e-12 * value.abs().max(1.0);
+            if close || rest == 0.0 {
+                return Rational::reduce(h, k);
+            }
+            rest = 1.0 / rest;
+            let term = rest.floor() as i128;
+            rest -= rest.floor();
+            (h, h_previous) = (term * h + h_previous, h);
+            (k, k_previous) = (term * k + k_previous, k);
+            if k > MAX_DENOMINATOR as i128 {
+                return None;
+            }
+        }
+    }
+
+    pub fn numerator(self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> i64 {
+        self.denominator
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// `None` when the result doesn't fit.
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let (a, b) = (self.numerator as i128, self.denominator as i128);
+        let (c, d) = (other.numerator as i128, other.denominator as i128);
+        Rational::reduce(a * d + c * b, b * d)
+    }
+
+    /// `None` when the result doesn't fit.
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        Rational::reduce(
+            self.numerator as i128 * other.numerator as i128,
+            self.denominator as i128 * other.denominator as i128,
+        )
+    }
+
+    /// `None` when dividing by zero or the result doesn't fit.
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        Rational::reduce(
+            self.numerator as i128 * other.denominator as i128,
+            self.denominator as i128 * other.numerator as i128,
+        )
+    }
+
+    /// `base` raised to this power. Odd roots of negative numbers are real, so
+    /// `(-8)^(1/3)` is `-2` and `(-8)^(2/3)` is `4`.
+    pub fn raise(self, base: f64) -> f64 {
+        if base < 0.0 && self.denominator % 2 == 1 {
+            let magnitude = (-base).powf(self.to_f64());
+            return if self.numerator % 2 == 0 {
+                magnitude
+            } else {
+                -magnitude
+            };
+        }
+        base.powf(self.to_f64())
+    }
+
+    fn reduce(numerator: i128, denominator: i128) -> Option<Rational> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Some(Rational {
+            numerator: (numerator / divisor).try_into().ok()?,
+            denominator: (denominator / divisor).try_into().ok()?,
+        })
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            denominator => write!(f, "{}/{denominator}", self.numerator),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+/// The `n`th root of `x`. Odd roots of negative numbers are real, so `root(-8, 3)`
+/// is `-2` rather than `NaN`.
+pub fn root(x: f64, n: f64) -> f64 {
+    if x < 0.0 && n.fract() == 0.0 && n % 2.0 != 0.0 {
+        -(-x).powf(1.0 / n)
+    } else {
+        x.powf(1.0 / n)
+    }
+}
@@ -1,4 +1,24 @@
+/// Whether `a` and `b` are equal up to the rounding error of a few operations, so
+/// `0.1 + 0.2` equals `0.3`. The tolerance is relative to their size, so tiny values
+/// such as physical constants still compare by their leading digits, except near zero
+/// where it is the precision results are rounded to.
+pub fn nearly_equal(a: f64, b: f64) -> bool {
+    if a == b {
+        return true;
+    }
+    let tolerance = match a == 0.0 || b == 0.0 {
+        true => 1e-15,
+        false => 1e-12 * f64::max(a.abs(), b.abs()),
+    };
+    a.is_finite() && b.is_finite() && (a - b).abs() <= tolerance
+}
+
 pub fn round(x: f64, decimals: u32) -> f64 {
     let y = 10i64.pow(decimals) as f64;
-    (x * y).round() / y
+    let scaled = x * y;
+    // Values too big to scale have no decimals left to round
+    if !scaled.is_finite() {
+        return x;
+    }
+    scaled.round() / y
 }
@@ -0,0 +1,102 @@
+use super::round;
+
+/// Most transformation orders tried. Higher orders lose more digits to cancellation
+/// than they gain.
+const MAX_ORDER: usize = 30;
+
+/// Longest list a range may produce, and most terms of a finite sum or product.
+const MAX_RANGE_LENGTH: f64 = 1e6;
+
+/// Relative change between consecutive estimates below which the limit is trusted.
+const TOLERANCE: f64 = 1e-8;
+
+/// How much larger than the last change between estimates their error may be.
+const ERROR_MARGIN: f64 = 10.0;
+
+/// Estimates the limit of a converging sequence, such as the partial sums of an
+/// infinite series, with the Levin u-transform. It accelerates alternating, geometric
+/// and the slowly converging series like `1/k^2` alike. Nothing is returned when the
+/// sequence doesn't settle, which is the case for divergent series. The estimate is
+/// rounded to the decimals that settled, so every digit of it can be trusted.
+pub fn extrapolate_limit(sequence: &[f64]) -> Option<f64> {
+    if sequence.len() < 3 || sequence.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+    let terms: Vec<f64> = (0..sequence.len())
+        .map(|n| match n {
+            0 => sequence[0],
+            n => sequence[n] - sequence[n - 1],
+        })
+        .collect();
+
+    // A sequence that stops changing needs no extrapolation, while one whose steps
+    // don't shrink can't converge
+    let last = terms.len() - 1;
+    if terms[last / 2..].iter().all(|term| *term == 0.0) {
+        return Some(sequence[last]);
+    }
+    if terms[last].abs() >= terms[last / 2].abs() {
+        return None;
+    }
+
+    let mut best: Option<(f64, f64)> = None;
+    let mut previous: Option<f64> = None;
+    for order in 1..MAX_ORDER.min(last) {
+        let estimate = levin(sequence, &terms, order);
+        if let Some(previous) = previous {
+            let change = (estimate - previous).abs() / f64::max(1.0, estimate.abs());
+            if change.is_finite() && best.is_none_or(|(_, best)| change < best) {
+                best = Some((estimate, change));
+            }
+        }
+        previous = Some(estimate);
+    }
+
+    match best {
+        Some((estimate, change)) if change <= TOLERANCE => {
+            let error = ERROR_MARGIN * change * f64::max(1.0, estimate.abs());
+            if error == 0.0 {
+                return Some(estimate);
+            }
+            let decimals = (-error.log10()).floor().clamp(0.0, 15.0);
+            Some(round(estimate, decimals as u32))
+        }
+        _ => None,
+    }
+}
+
+/// Levin u-transform of the given order, using the remainder estimate `(n + 1) a_n`.
+fn levin(sequence: &[f64], terms: &[f64], order: usize) -> f64 {
+    let mut numerator = 0.0;
+    let mut denominator = 0.0;
+    let mut binomial = 1.0;
+    for j in 0..=order {
+        if j > 0 {
+            binomial *= (order + 1 - j) as f64 / j as f64;
+        }
+        if terms[j] == 0.0 {
+            continue;
+        }
+        let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+        let weight = sign * binomial * ((j + 1) as f64 / (order + 1) as f64).powi(order as i32 - 1);
+        let remainder = (j + 1) as f64 * terms[j];
+        numerator += weight * sequence[j] / remainder;
+        denominator += weight / remainder;
+    }
+    numerator / denominator
+}
+
+/// Number of steps of one from `start` up to `end`, counting both ends. `None` when
+/// they are more than [`MAX_RANGE_LENGTH`] apart or so large that adding one no longer
+/// changes them.
+pub fn step_count(start: f64, end: f64) -> Option<u64> {
+    let largest = start.abs().max(end.abs());
+    if !largest.is_finite() || largest >= 2f64.powi(f64::MANTISSA_DIGITS as i32) {
+        return None;
+    }
+    match end - start {
+        distance if distance > MAX_RANGE_LENGTH => None,
+        distance if distance < 0.0 => Some(0),
+        distance => Some(distance.floor() as u64 + 1),
+    }
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::nearly_equal;
+
+/// Interval of the real line. Infinite ends are always open. Nearly equal ends are
+/// compared as equal, so `0.1 + 0.2` is in `[0.3, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+    pub lower_closed: bool,
+    pub upper_closed: bool,
+}
+
+impl Interval {
+    pub fn new(lower: f64, upper: f64, lower_closed: bool, upper_closed: bool) -> Interval {
+        Interval {
+            lower,
+            upper,
+            lower_closed: lower_closed && lower.is_finite(),
+            upper_closed: upper_closed && upper.is_finite(),
+        }
+    }
+
+    /// The interval `[value, value]` holding a single number.
+    pub fn point(value: f64) -> Interval {
+        Interval::new(value, value, true, true)
+    }
+
+    pub fn is_point(&self) -> bool {
+        nearly_equal(self.lower, self.upper) && self.lower_closed && self.upper_closed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        if nearly_equal(self.lower, self.upper) {
+            return !(self.lower_closed && self.upper_closed);
+        }
+        self.lower > self.upper
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        let above_lower = if nearly_equal(self.lower, value) {
+            self.lower_closed
+        } else {
+            self.lower < value
+        };
+        let below_upper = if nearly_equal(value, self.upper) {
+            self.upper_closed
+        } else {
+            value < self.upper
+        };
+        above_lower && below_upper
+    }
+
+    fn intersection(&self, other: &Interval) -> Interval {
+        let (lower, lower_closed) = match compare(self.lower, other.lower) {
+            Ordering::Less => (other.lower, other.lower_closed),
+            Ordering::Greater => (self.lower, self.lower_closed),
+            Ordering::Equal => (self.lower, self.lower_closed && other.lower_closed),
+        };
+        let (upper, upper_closed) = match compare(self.upper, other.upper) {
+            Ordering::Less => (self.upper, self.upper_closed),
+            Ordering::Greater => (other.upper, other.upper_closed),
+            Ordering::Equal => (self.upper, self.upper_closed && other.upper_closed),
+        };
+        Interval::new(lower, upper, lower_closed, upper_closed)
+    }
+
+    /// Whether `next`, which starts no earlier, overlaps or touches this interval so
+    /// that their union is a single interval.
+    fn joins(&self, next: &Interval) -> bool {
+        if nearly_equal(next.lower, self.upper) {
+            return self.upper_closed || next.lower_closed;
+        }
+        next.lower < self.upper
+    }
+}
+
+/// Orders interval ends, with nearly equal ones as equal.
+fn compare(a: f64, b: f64) -> Ordering {
+    if nearly_equal(a, b) {
+        return Ordering::Equal;
+    }
+    a.total_cmp(&b)
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let open = if self.lower_closed { '[' } else { '(' };
+        let close = if self.upper_closed { ']' } else { ')' };
+        write!(f, "{open}{}, {}{close}", self.lower, self.upper)
+    }
+}
+
+/// Set of real numbers made of intervals and single points, such as
+/// `(-inf, -2) ∪ {0} ∪ (2, inf)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Set {
+    /// Sorted, non-empty and separated by gaps, so every set has one representation.
+    intervals: Vec<Interval>,
+}
+
+impl Set {
+    /// The union of `intervals`, which may overlap or be empty.
+    pub fn new(mut intervals: Vec<Interval>) -> Set {
+        intervals.retain(|interval| !interval.is_empty());
+        // Closed lower ends first, so they survive the merge
+        intervals.sort_by(|a, b| {
+            a.lower
+                .total_cmp(&b.lower)
+                .then(b.lower_closed.cmp(&a.lower_closed))
+        });
+
+        let mut merged: Vec<Interval> = Vec::new();
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if last.joins(&interval) => match compare(interval.upper, last.upper) {
+                    Ordering::Greater => {
+                        last.upper = interval.upper;
+                        last.upper_closed = interval.upper_closed;
+                    }
+                    Ordering::Equal => last.upper_closed |= interval.upper_closed,
+                    Ordering::Less => {}
+                },
+                _ => merged.push(interval),
+            }
+        }
+        Set { intervals: merged }
+    }
+
+    /// A finite set such as `{1, 2, 3}`.
+    pub fn from_points(points: &[f64]) -> Set {
+        Set::new(points.iter().copied().map(Interval::point).collect())
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The set with `function` applied to the ends of its intervals, which must keep
+    /// their order.
+    pub fn map(&self, function: impl Fn(f64) -> f64) -> Set {
+        let intervals = self.intervals.iter().map(|interval| {
+            Interval::new(
+                function(interval.lower),
+                function(interval.upper),
+                interval.lower_closed,
+                interval.upper_closed,
+            )
+        });
+        Set::new(intervals.collect())
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.intervals
+            .iter()
+            .any(|interval| interval.contains(value))
+    }
+
+    pub fn union(&self, other: &Set) -> Set {
+        Set::new([self.intervals.as_slice(), &other.intervals].concat())
+    }
+
+    pub fn intersection(&self, other: &Set) -> Set {
+        let mut intervals = Vec::new();
+        for a in &self.intervals {
+            for b in &other.intervals {
+                intervals.push(a.intersection(b));
+            }
+        }
+        Set::new(intervals)
+    }
+
+    /// Every real number that isn't in the set.
+    pub fn complement(&self) -> Set {
+        let mut gaps = Vec::new();
+        let (mut lower, mut lower_closed) = (f64::NEG_INFINITY, false);
+        for interval in &self.intervals {
+            gaps.push(Interval::new(
+                lower,
+                interval.lower,
+                lower_closed,
+                !interval.lower_closed,
+            ));
+            (lower, lower_closed) = (interval.upper, !interval.upper_closed);
+        }
+        gaps.push(Interval::new(lower, f64::INFINITY, lower_closed, false));
+        Set::new(gaps)
+    }
+
+    pub fn difference(&self, other: &Set) -> Set {
+        self.intersection(&other.complement())
+    }
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.intervals.is_empty() {
+            return write!(f, "{{}}");
+        }
+
+        // Neighbouring single points are written as one finite set
+        let mut parts = Vec::new();
+        let mut points = Vec::new();
+        for interval in &self.intervals {
+            if interval.is_point() {
+                points.push(interval.lower.to_string());
+                continue;
+            }
+            if !points.is_empty() {
+                parts.push(format!("{{{}}}", points.join(", ")));
+                points.clear();
+            }
+            parts.push(interval.to_string());
+        }
+        if !points.is_empty() {
+            parts.push(format!("{{{}}}", points.join(", ")));
+        }
+        write!(f, "{}", parts.join(" ∪ "))
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock, RwLockWriteGuard};
+
+use super::Value;
+
+/// Variables assigned with `name := value`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environment {
+    variables: HashMap<String, Value>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.variables.remove(name)
+    }
+
+    pub fn clear(&mut self) {
+        self.variables.clear();
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref ENVIRONMENT: RwLock<Environment> = RwLock::new(Environment::new());
+}
+
+/// The environment shared by [`evaluate`](super::evaluate) calls, so variables assigned
+/// in one input can be read by the next one. The variables stay usable after a panic
+/// while the guard was held, since every write leaves them consistent.
+pub fn environment() -> RwLockWriteGuard<'static, Environment> {
+    ENVIRONMENT.write().unwrap_or_else(PoisonError::into_inner)
+}
@@ -1,96 +1,998 @@
+use std::collections::HashMap;
+
 use anyhow::{bail, Result};
 
-use crate::error::EvaluatorError;
-use crate::math::{deg_to_rad, round};
-use crate::parser::{parse, Expr, Op};
+use crate::error::{EvaluatorError, ParserError};
+use crate::math::{
+    deg_to_rad, double_factorial, extrapolate_limit, factorial, gamma, nearly_equal, root, round,
+    step_count, Complex, Interval, Matrix, Rational, Set,
+};
+use crate::parser::{
+    parse_config, parse_latex, parse_statements_with_config, Expr, Logic, Op, ParseConfig,
+    Relation, Series, SetOp, Span, Statement, UnaryOp,
+};
+
+use super::{environment, Environment, UserFunction, Value};
+
+/// How deeply user defined functions may call each other before evaluation gives up.
+const MAX_CALL_DEPTH: usize = 64;
+
+/// Partial sums or products an infinite series is extrapolated from.
+const SERIES_TERMS: usize = 40;
+
+/// Variables visible while evaluating. The parameters of the user defined function
+/// being called shadow the variables of the environment.
+struct Scope<'a> {
+    env: &'a Environment,
+    locals: HashMap<String, Value>,
+    depth: usize,
+}
+
+impl<'a> Scope<'a> {
+    fn new(env: &'a Environment) -> Scope<'a> {
+        Scope {
+            env,
+            locals: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.locals.get(name).or_else(|| self.env.get(name))
+    }
+}
+
+fn check_arity(name: &str, args: &[Expr], expected: usize, span: Span) -> Result<()> {
+    if args.len() != expected {
+        bail!(EvaluatorError::ArgumentCount(
+            name.to_string(),
+            expected,
+            args.len(),
+            span
+        ));
+    }
+    Ok(())
+}
+
+fn evaluate_number(expr: Expr, scope: &Scope) -> Result<f64> {
+    let span = expr.span();
+    evaluate_expr(expr, scope)?.into_number(span)
+}
+
+/// Evaluates the elements of a list argument, which all have to be numbers.
+fn evaluate_numbers(expr: Expr, scope: &Scope) -> Result<Vec<f64>> {
+    let span = expr.span();
+    evaluate_expr(expr, scope)?
+        .into_list(span)?
+        .into_iter()
+        .map(|item| item.into_number(span))
+        .collect()
+}
+
+/// Applies a function of one number to its argument, element-wise for lists.
+fn map_arg(
+    name: &str,
+    args: Vec<Expr>,
+    span: Span,
+    scope: &Scope,
+    function: impl Fn(f64) -> f64 + Copy,
+) -> Result<Value> {
+    check_arity(name, &args, 1, span)?;
+    let arg = args[0].to_owned();
+    evaluate_expr(arg, scope)?.map(span, function)
+}
+
+/// Applies a function of two numbers to its arguments, element-wise for lists.
+fn zip_args(
+    name: &str,
+    args: Vec<Expr>,
+    span: Span,
+    scope: &Scope,
+    function: impl Fn(f64, f64) -> f64 + Copy,
+) -> Result<Value> {
+    check_arity(name, &args, 2, span)?;
+    let mut args = args.into_iter();
+    let arg1 = args.next().unwrap();
+    let arg2 = args.next().unwrap();
+    evaluate_expr(arg1, scope)?.zip(evaluate_expr(arg2, scope)?, span, function)
+}
+
+/// `min(a, b)` compares two values, `min(v)` finds the smallest element of a list.
+fn extremum(
+    name: &str,
+    args: Vec<Expr>,
+    span: Span,
+    scope: &Scope,
+    function: fn(f64, f64) -> f64,
+) -> Result<Value> {
+    match args.len() {
+        1 => {
+            let arg = args[0].to_owned();
+            let numbers = evaluate_numbers(arg, scope)?;
+            Ok(Value::Number(
+                numbers.into_iter().reduce(function).unwrap_or(f64::NAN),
+            ))
+        }
+        _ => zip_args(name, args, span, scope, function),
+    }
+}
+
+/// Whole numbers from `start` up to and including `end`.
+fn range(start: f64, end: f64, span: Span) -> Result<Value> {
+    let count = match step_count(start, end) {
+        Some(count) => count,
+        None => bail!(EvaluatorError::InvalidRange(span)),
+    };
+    let items = (0..count)
+        .map(|step| Value::Number(start + step as f64))
+        .collect();
+    Ok(Value::List(items))
+}
+
+/// A list of numbers as a column vector.
+fn column(items: Vec<Value>, span: Span) -> Result<Matrix> {
+    let size = items.len();
+    let data = items
+        .into_iter()
+        .map(|item| item.into_number(span))
+        .collect::<Result<Vec<f64>>>()?;
+    Ok(Matrix::new(size, 1, data))
+}
+
+/// A single row or column matrix as a list of numbers.
+fn vector(matrix: Matrix) -> Value {
+    Value::List(
+        matrix
+            .into_rows()
+            .concat()
+            .into_iter()
+            .map(Value::Number)
+            .collect(),
+    )
+}
+
+fn product(lhs: &Matrix, rhs: &Matrix, span: Span) -> Result<Matrix> {
+    match lhs.mul(rhs) {
+        Some(product) => Ok(product),
+        None => bail!(EvaluatorError::ShapeMismatch(
+            lhs.shape(),
+            rhs.shape(),
+            span
+        )),
+    }
+}
+
+/// `*` is the matrix product as soon as a matrix is involved. Lists are column vectors
+/// on the right hand side and row vectors on the left hand side.
+fn multiply(lhs: Value, rhs: Value, span: Span) -> Result<Value> {
+    match (lhs, rhs) {
+        (Value::Matrix(lhs), Value::Matrix(rhs)) => Ok(Value::Matrix(product(&lhs, &rhs, span)?)),
+        (Value::Matrix(lhs), Value::List(rhs)) => {
+            Ok(vector(product(&lhs, &column(rhs, span)?, span)?))
+        }
+        (Value::List(lhs), Value::Matrix(rhs)) => Ok(vector(product(
+            &column(lhs, span)?.transpose(),
+            &rhs,
+            span,
+        )?)),
+        (lhs, rhs) => lhs.zip(rhs, span, |lhs, rhs| lhs * rhs),
+    }
+}
+
+/// Square matrices are raised to whole powers by repeated multiplication, `A^-1` is the
+/// inverse.
+fn power(lhs: Value, rhs: Value, span: Span) -> Result<Value> {
+    let matrix = match lhs {
+        Value::Matrix(matrix) => matrix,
+        lhs => return lhs.zip(rhs, span, f64::powf),
+    };
+    let exponent = rhs.into_number(span)?;
+    if exponent.fract() != 0.0 || exponent.abs() > i32::MAX as f64 {
+        bail!(EvaluatorError::MatrixPower(span));
+    }
+    let matrix = square(matrix, span)?;
+    match matrix.pow(exponent as i32) {
+        Some(matrix) => Ok(Value::Matrix(matrix)),
+        None => bail!(EvaluatorError::SingularMatrix(span)),
+    }
+}
+
+fn square(matrix: Matrix, span: Span) -> Result<Matrix> {
+    if !matrix.is_square() {
+        bail!(EvaluatorError::NotSquare(matrix.shape(), span));
+    }
+    Ok(matrix)
+}
+
+fn matrix_arg(name: &str, args: Vec<Expr>, span: Span, scope: &Scope) -> Result<Matrix> {
+    check_arity(name, &args, 1, span)?;
+    let arg = args[0].to_owned();
+    let arg_span = arg.span();
+    evaluate_expr(arg, scope)?.into_matrix(arg_span)
+}
+
+/// A real eigenvalue is a number, the others complex.
+fn complex(value: Complex) -> Value {
+    match value.im == 0.0 {
+        true => Value::Number(value.re),
+        false => Value::Complex(value),
+    }
+}
+
+/// `eig(A)` gives a list of the eigenvalues followed by a list of the matching
+/// eigenvectors.
+fn eigen(matrix: Matrix, span: Span) -> Result<Value> {
+    let pairs = match matrix.eigen() {
+        Some(pairs) => pairs,
+        None => bail!(EvaluatorError::NoConvergence(span)),
+    };
+    let (values, vectors): (Vec<Value>, Vec<Value>) = pairs
+        .into_iter()
+        .map(|(value, vector)| {
+            (
+                complex(value),
+                Value::List(vector.into_iter().map(complex).collect()),
+            )
+        })
+        .unzip();
+    Ok(Value::List(vec![Value::List(values), Value::List(vectors)]))
+}
+
+/// Solves `A * X = B`. A list on the right hand side gives a list as the solution.
+fn solve(name: &str, args: Vec<Expr>, span: Span, scope: &Scope) -> Result<Value> {
+    check_arity(name, &args, 2, span)?;
+    let mut args = args.into_iter();
+    let matrix = args.next().unwrap();
+    let matrix_span = matrix.span();
+    let matrix = square(
+        evaluate_expr(matrix, scope)?.into_matrix(matrix_span)?,
+        span,
+    )?;
+    let rhs = args.next().unwrap();
+    let rhs_span = rhs.span();
+    let (rhs, is_vector) = match evaluate_expr(rhs, scope)? {
+        Value::List(items) => (column(items, rhs_span)?, true),
+        value => (value.into_matrix(rhs_span)?, false),
+    };
+    if rhs.rows() != matrix.rows() {
+        bail!(EvaluatorError::ShapeMismatch(
+            matrix.shape(),
+            rhs.shape(),
+            span
+        ));
+    }
+
+    match (matrix.solve(&rhs), is_vector) {
+        (Some(solution), true) => Ok(vector(solution)),
+        (Some(solution), false) => Ok(Value::Matrix(solution)),
+        (None, _) => bail!(EvaluatorError::SingularMatrix(span)),
+    }
+}
+
+fn index(target: Value, indices: Vec<Expr>, span: Span, scope: &Scope) -> Result<Value> {
+    let mut value = target;
+    for index in indices {
+        let index_span = index.span();
+        // `A[2]` is the second row of a matrix and `A[2, 1]` its first entry
+        let mut items = match value {
+            Value::Matrix(matrix) => matrix
+                .into_rows()
+                .into_iter()
+                .map(|row| Value::List(row.into_iter().map(Value::Number).collect()))
+                .collect(),
+            value => value.into_list(span)?,
+        };
+        let position = evaluate_number(index, scope)?;
+        if position.fract() != 0.0 || position < 1.0 || position > items.len() as f64 {
+            bail!(EvaluatorError::IndexOutOfRange(
+                position.to_string(),
+                items.len(),
+                index_span
+            ));
+        }
+        value = items.swap_remove(position as usize - 1);
+    }
+    Ok(value)
+}
+
+/// Evaluates only the value of the first case whose condition holds, so the other
+/// branches may fail or recurse without end.
+fn piecewise(
+    cases: Vec<(Expr, Expr)>,
+    otherwise: Option<Expr>,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    for (value, condition) in cases {
+        let condition_span = condition.span();
+        if evaluate_expr(condition, scope)?.into_bool(condition_span)? {
+            return evaluate_expr(value, scope);
+        }
+    }
+    match otherwise {
+        Some(otherwise) => evaluate_expr(otherwise, scope),
+        None => bail!(EvaluatorError::NoMatchingCase(span)),
+    }
+}
+
+/// `base + share%` or `base - share%`, where the share is a percentage of the base.
+fn percentage(base: Expr, op: Op, share: Expr, span: Span, scope: &Scope) -> Result<Value> {
+    let base = evaluate_expr(base, scope)?;
+    let share = base
+        .clone()
+        .zip(evaluate_expr(share, scope)?, span, |base, share| {
+            base * share
+        })?;
+    match op {
+        Op::Add => base.zip(share, span, |base, share| base + share),
+        _ => base.zip(share, span, |base, share| base - share),
+    }
+}
+
+/// `/` divides element-wise, except between two matrices where `A / B` would read as
+/// `A * inv(B)` next to the matrix product.
+fn divide(lhs: Value, rhs: Value, span: Span) -> Result<Value> {
+    match (lhs, rhs) {
+        (Value::Matrix(_), Value::Matrix(_)) => bail!(EvaluatorError::MatrixDivision(span)),
+        (lhs, rhs) => lhs.zip(rhs, span, |lhs, rhs| lhs / rhs),
+    }
+}
+
+fn binary(lhs: Expr, op: Op, rhs: Expr, span: Span, scope: &Scope) -> Result<Value> {
+    let lhs = evaluate_expr(lhs, scope)?;
+    let rhs = evaluate_expr(rhs, scope)?;
+    match op {
+        Op::Add => lhs.zip(rhs, span, |lhs, rhs| lhs + rhs),
+        Op::Subtract => lhs.zip(rhs, span, |lhs, rhs| lhs - rhs),
+        Op::Multiply => multiply(lhs, rhs, span),
+        Op::Divide => divide(lhs, rhs, span),
+        Op::Modulo => lhs.zip(rhs, span, |lhs, rhs| (lhs % rhs).abs()),
+        Op::Power => power(lhs, rhs, span),
+    }
+}
 
-fn evaluate_expr(expr: Expr) -> Result<f64> {
+/// Evaluates a monomial such as `3X^2Y`, whose factors multiply element-wise. A lone
+/// variable keeps whatever kind of value was assigned to it.
+fn monomial(
+    coefficient: f64,
+    factors: Vec<(String, Rational)>,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let lone = coefficient == 1.0 && matches!(factors.as_slice(), [(_, Rational::ONE)]);
+    let mut product = Value::Number(coefficient);
+    for (variable, exponent) in factors {
+        let value = match scope.get(&variable) {
+            Some(value) if lone => return Ok(value.clone()),
+            Some(value) => value.clone().map(span, |value| exponent.raise(value))?,
+            None => bail!(EvaluatorError::UnknownVariable(variable, span)),
+        };
+        product = product.zip(value, span, |lhs, rhs| lhs * rhs)?;
+    }
+    Ok(product)
+}
+
+fn relation(operands: Vec<Expr>, relations: Vec<Relation>, scope: &Scope) -> Result<Value> {
+    let operands = operands
+        .into_iter()
+        .map(|operand| {
+            let span = operand.span();
+            Ok((evaluate_expr(operand, scope)?, span))
+        })
+        .collect::<Result<Vec<(Value, Span)>>>()?;
+    let mut holds = true;
+    for (relation, pair) in relations.iter().zip(operands.windows(2)) {
+        holds &= compare(*relation, &pair[0], &pair[1])?;
+    }
+    Ok(Value::Bool(holds))
+}
+
+/// Compares two operands of a relation, which are numbers or, for `=` and `!=` only,
+/// booleans.
+fn compare(relation: Relation, lhs: &(Value, Span), rhs: &(Value, Span)) -> Result<bool> {
+    if let ((Value::Bool(lhs_value), span), (Value::Bool(rhs_value), _)) = (lhs, rhs) {
+        return match relation.holds_bool(*lhs_value, *rhs_value) {
+            Some(holds) => Ok(holds),
+            None => bail!(EvaluatorError::ExpectedNumber(
+                lhs.0.kind().to_string(),
+                *span
+            )),
+        };
+    }
+    let lhs = lhs.0.clone().into_number(lhs.1)?;
+    let rhs = rhs.0.clone().into_number(rhs.1)?;
+    Ok(relation.holds(lhs, rhs))
+}
+
+fn not(operand: Expr, scope: &Scope) -> Result<Value> {
+    let span = operand.span();
+    Ok(Value::Bool(
+        !evaluate_expr(operand, scope)?.into_bool(span)?,
+    ))
+}
+
+/// Evaluates a logical connective. `and`, `or` and `implies` skip the right hand
+/// side once the left one decides the result.
+fn logic(lhs: Expr, op: Logic, rhs: Expr, scope: &Scope) -> Result<Value> {
+    let span = lhs.span();
+    let lhs = evaluate_expr(lhs, scope)?.into_bool(span)?;
+    let result = match (op, lhs) {
+        (Logic::And, false) => false,
+        (Logic::Or, true) | (Logic::Implies, false) => true,
+        _ => {
+            let span = rhs.span();
+            op.apply(lhs, evaluate_expr(rhs, scope)?.into_bool(span)?)
+        }
+    };
+    Ok(Value::Bool(result))
+}
+
+/// Evaluates an interval. Ends that are nearly equal, like `[0.3, 0.1 + 0.2]`, make a
+/// single point rather than an invalid interval.
+fn interval(
+    lower: Expr,
+    upper: Expr,
+    (lower_closed, upper_closed): (bool, bool),
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let lower = evaluate_number(lower, scope)?;
+    let upper = evaluate_number(upper, scope)?;
+    if lower > upper && !nearly_equal(lower, upper) {
+        bail!(EvaluatorError::InvalidInterval(span));
+    }
+    let interval = Interval::new(lower, upper, lower_closed, upper_closed);
+    Ok(Value::Set(Set::new(vec![interval])))
+}
+
+fn set_operation(lhs: Expr, op: SetOp, rhs: Expr, scope: &Scope) -> Result<Value> {
+    let (lhs_span, rhs_span) = (lhs.span(), rhs.span());
+    let lhs = evaluate_expr(lhs, scope)?;
+    let rhs = evaluate_expr(rhs, scope)?.into_set(rhs_span)?;
+    if op == SetOp::Element {
+        let value = lhs.into_number(lhs_span)?;
+        return Ok(Value::Bool(rhs.contains(value)));
+    }
+    let lhs = lhs.into_set(lhs_span)?;
+    Ok(Value::Set(match op {
+        SetOp::Union => lhs.union(&rhs),
+        SetOp::Intersection => lhs.intersection(&rhs),
+        _ => lhs.difference(&rhs),
+    }))
+}
+
+/// Evaluates a sum or product with `variable` bound to every whole number from
+/// `lower` to `upper`. Infinite series are extrapolated from their first terms.
+fn series(
+    kind: Series,
+    variable: String,
+    (lower, upper): (Expr, Expr),
+    body: Expr,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let lower = evaluate_number(lower, scope)?;
+    let upper = evaluate_number(upper, scope)?;
+    let whole = |bound: f64| bound.is_infinite() || bound.fract() == 0.0;
+    if !whole(lower) || !whole(upper) || lower == f64::INFINITY || upper == f64::NEG_INFINITY {
+        bail!(EvaluatorError::InvalidBounds(span));
+    }
+
+    let mut inner = Scope {
+        env: scope.env,
+        locals: scope.locals.clone(),
+        depth: scope.depth,
+    };
+    let mut term = |index: f64| {
+        inner.locals.insert(variable.clone(), Value::Number(index));
+        evaluate_number(body.clone(), &inner)
+    };
+    let combine = |total: f64, term: f64| match kind {
+        Series::Sum => total + term,
+        Series::Product => total * term,
+    };
+    let mut total = match kind {
+        Series::Sum => 0.0,
+        Series::Product => 1.0,
+    };
+
+    if lower.is_finite() && upper.is_finite() {
+        let count = match step_count(lower, upper) {
+            Some(count) => count,
+            None => bail!(EvaluatorError::InvalidBounds(span)),
+        };
+        for step in 0..count {
+            total = combine(total, term(lower + step as f64)?);
+        }
+        return Ok(Value::Number(total));
+    }
+
+    // An infinite bound on both sides pairs up `k` and `-k`
+    let mut sequence = Vec::with_capacity(SERIES_TERMS);
+    for i in 0..SERIES_TERMS {
+        let i = i as f64;
+        let next = match (lower.is_finite(), upper.is_finite()) {
+            (true, _) => term(lower + i)?,
+            (_, true) => term(upper - i)?,
+            _ if i == 0.0 => term(0.0)?,
+            _ => combine(term(i)?, term(-i)?),
+        };
+        total = combine(total, next);
+        sequence.push(total);
+    }
+    match extrapolate_limit(&sequence) {
+        Some(limit) => Ok(Value::Number(limit)),
+        None => bail!(EvaluatorError::Divergent(span)),
+    }
+}
+
+fn evaluate_expr(expr: Expr, scope: &Scope) -> Result<Value> {
     match expr {
-        Expr::BinOp { lhs, op, rhs } => match op {
-            Op::Add => Ok(evaluate_expr(*lhs)? + evaluate_expr(*rhs)?),
-            Op::Subtract => Ok(evaluate_expr(*lhs)? - evaluate_expr(*rhs)?),
-            Op::Multiply => Ok(evaluate_expr(*lhs)? * evaluate_expr(*rhs)?),
-            Op::Divide => Ok(evaluate_expr(*lhs)? / evaluate_expr(*rhs)?),
-            Op::Modulo => Ok((evaluate_expr(*lhs)? % evaluate_expr(*rhs)?).abs()),
-            Op::Power => Ok(evaluate_expr(*lhs)?.powf(evaluate_expr(*rhs)?)),
-            Op::Equals => bail!(EvaluatorError::EqualityInEval),
-        },
-        Expr::Number(val) => Ok(val),
-        Expr::Constant { value, .. } => Ok(value),
-        Expr::UnaryMinus(op) => Ok(-1.0 * evaluate_expr(*op)?),
-        Expr::Function { name, args } => match name.as_str() {
-            "cos" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).cos())
-            }
-            "sin" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).sin())
+        // Calculator style percentages, `50 + 10%` adds ten percent of 50 to it
+        Expr::BinOp {
+            lhs,
+            op: op @ (Op::Add | Op::Subtract),
+            rhs,
+            span,
+        } if matches!(
+            *rhs,
+            Expr::UnaryOp {
+                op: UnaryOp::Percent,
+                ..
             }
-            "tan" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(deg_to_rad(evaluate_expr(*arg)?).tan())
+        ) =>
+        {
+            percentage(*lhs, op, *rhs, span, scope)
+        }
+        Expr::BinOp { lhs, op, rhs, span } => binary(*lhs, op, *rhs, span, scope),
+        Expr::Relation {
+            operands,
+            relations,
+            ..
+        } => relation(operands, relations, scope),
+        Expr::Series {
+            kind,
+            variable,
+            lower,
+            upper,
+            body,
+            span,
+        } => series(kind, variable, (*lower, *upper), *body, span, scope),
+        Expr::Piecewise {
+            cases,
+            otherwise,
+            span,
+        } => piecewise(cases, otherwise.map(|otherwise| *otherwise), span, scope),
+        Expr::Lambda { params, body, .. } => Ok(Value::Function(UserFunction {
+            params,
+            body: *body,
+            captured: scope.locals.clone().into_iter().collect(),
+        })),
+        Expr::Number(val, _) => Ok(Value::Number(val)),
+        Expr::Bool(val, _) => Ok(Value::Bool(val)),
+        Expr::Not(operand, _) => not(*operand, scope),
+        Expr::Logic { lhs, op, rhs, .. } => logic(*lhs, op, *rhs, scope),
+        Expr::Interval {
+            lower,
+            upper,
+            lower_closed,
+            upper_closed,
+            span,
+        } => interval(*lower, *upper, (lower_closed, upper_closed), span, scope),
+        Expr::Set { items, .. } => {
+            let points = items
+                .into_iter()
+                .map(|item| evaluate_number(item, scope))
+                .collect::<Result<Vec<f64>>>()?;
+            Ok(Value::Set(Set::from_points(&points)))
+        }
+        Expr::SetOp { lhs, op, rhs, .. } => set_operation(*lhs, op, *rhs, scope),
+        Expr::Derivative { span, .. } => bail!(EvaluatorError::UnresolvedDerivative(span)),
+        Expr::Error(span) => bail!(EvaluatorError::ParseFailure(ParserError::ExpectedOperand(
+            span
+        ))),
+        Expr::Constant { value, .. } => Ok(Value::Number(value)),
+        Expr::UnaryMinus(op, span) => evaluate_expr(*op, scope)?.map(span, |val| -val),
+        Expr::UnaryOp { op, operand, span } => {
+            let operand = evaluate_expr(*operand, scope)?;
+            match op {
+                UnaryOp::Factorial => operand.map(span, factorial),
+                UnaryOp::DoubleFactorial => operand.map(span, double_factorial),
+                UnaryOp::Percent => operand.map(span, |val| val / 100.0),
             }
-            "floor" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.floor())
-            }
-            "ceil" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.ceil())
+        }
+        Expr::Operator {
+            operands, function, ..
+        } => {
+            let operands = operands
+                .into_iter()
+                .map(|operand| evaluate_number(operand, scope))
+                .collect::<Result<Vec<f64>>>()?;
+            Ok(Value::Number(function.call(&operands)))
+        }
+        Expr::List { items, .. } => Ok(Value::list(
+            items
+                .into_iter()
+                .map(|item| evaluate_expr(item, scope))
+                .collect::<Result<Vec<Value>>>()?,
+        )),
+        Expr::Index {
+            target,
+            indices,
+            span,
+        } => {
+            let target = evaluate_expr(*target, scope)?;
+            index(target, indices, span, scope)
+        }
+        Expr::Monomial {
+            coefficient,
+            factors,
+            span,
+        } => monomial(coefficient, factors, span, scope),
+        Expr::Function { name, args, span }
+            if matches!(scope.get(&name), Some(Value::Function(_))) =>
+        {
+            call_function(&name, args, span, scope)
+        }
+        Expr::Function { name, args, span } => evaluate_function(name, args, span, scope),
+    }
+}
+
+fn evaluate_function(name: String, args: Vec<Expr>, span: Span, scope: &Scope) -> Result<Value> {
+    match name.as_str() {
+        "cos" => map_arg(&name, args, span, scope, |val| deg_to_rad(val).cos()),
+        "sin" => map_arg(&name, args, span, scope, |val| deg_to_rad(val).sin()),
+        "tan" => map_arg(&name, args, span, scope, |val| deg_to_rad(val).tan()),
+        "ln" => map_arg(&name, args, span, scope, f64::ln),
+        "log" => map_arg(&name, args, span, scope, f64::log10),
+        "exp" => map_arg(&name, args, span, scope, f64::exp),
+        "floor" => map_arg(&name, args, span, scope, f64::floor),
+        "ceil" => map_arg(&name, args, span, scope, f64::ceil),
+        "round" => map_arg(&name, args, span, scope, f64::round),
+        "trunc" => map_arg(&name, args, span, scope, f64::trunc),
+        "fract" => map_arg(&name, args, span, scope, f64::fract),
+        "sqrt" => map_arg(&name, args, span, scope, f64::sqrt),
+        "cbrt" => map_arg(&name, args, span, scope, f64::cbrt),
+        "root" => zip_args(&name, args, span, scope, root),
+        "abs" => map_arg(&name, args, span, scope, f64::abs),
+        // The norm of a scalar is its absolute value, of a list its length
+        "norm" => {
+            check_arity(&name, &args, 1, span)?;
+            let arg = args[0].to_owned();
+            match evaluate_expr(arg, scope)? {
+                Value::List(items) => {
+                    let mut sum = 0.0;
+                    for item in items {
+                        sum += item.into_number(span)?.powi(2);
+                    }
+                    Ok(Value::Number(sum.sqrt()))
+                }
+                value => value.map(span, f64::abs),
             }
-            "round" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.round())
+        }
+        "gamma" => map_arg(&name, args, span, scope, gamma),
+        "pow" => zip_args(&name, args, span, scope, f64::powf),
+        "min" => extremum(&name, args, span, scope, f64::min),
+        "max" => extremum(&name, args, span, scope, f64::max),
+        "range" => {
+            check_arity(&name, &args, 2, span)?;
+            let mut args = args.into_iter();
+            let start = evaluate_number(args.next().unwrap(), scope)?;
+            let end = evaluate_number(args.next().unwrap(), scope)?;
+            range(start, end, span)
+        }
+        "len" => {
+            check_arity(&name, &args, 1, span)?;
+            let arg = args[0].to_owned();
+            let arg_span = arg.span();
+            let items = evaluate_expr(arg, scope)?.into_list(arg_span)?;
+            Ok(Value::Number(items.len() as f64))
+        }
+        "sum" => {
+            check_arity(&name, &args, 1, span)?;
+            let arg = args[0].to_owned();
+            // Summed from `0.0`, `iter().sum()` would give `-0` for an empty list
+            let numbers = evaluate_numbers(arg, scope)?;
+            Ok(Value::Number(numbers.iter().fold(0.0, |sum, value| sum + value)))
+        }
+        "prod" => {
+            check_arity(&name, &args, 1, span)?;
+            let arg = args[0].to_owned();
+            Ok(Value::Number(
+                evaluate_numbers(arg, scope)?.iter().product(),
+            ))
+        }
+        "mean" => {
+            check_arity(&name, &args, 1, span)?;
+            let arg = args[0].to_owned();
+            let numbers = evaluate_numbers(arg, scope)?;
+            Ok(Value::Number(
+                numbers.iter().sum::<f64>() / numbers.len() as f64,
+            ))
+        }
+        "det" => {
+            let matrix = square(matrix_arg(&name, args, span, scope)?, span)?;
+            Ok(Value::Number(matrix.det()))
+        }
+        "inv" => {
+            let matrix = square(matrix_arg(&name, args, span, scope)?, span)?;
+            match matrix.inverse() {
+                Some(inverse) => Ok(Value::Matrix(inverse)),
+                None => bail!(EvaluatorError::SingularMatrix(span)),
             }
-            "trunc" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.trunc())
+        }
+        "transpose" => Ok(Value::Matrix(
+            matrix_arg(&name, args, span, scope)?.transpose(),
+        )),
+        "trace" => {
+            let matrix = square(matrix_arg(&name, args, span, scope)?, span)?;
+            Ok(Value::Number(matrix.trace()))
+        }
+        "rank" => Ok(Value::Number(
+            matrix_arg(&name, args, span, scope)?.rank() as f64
+        )),
+        "rref" => Ok(Value::Matrix(
+            matrix_arg(&name, args, span, scope)?.rref().0,
+        )),
+        "solve" => solve(&name, args, span, scope),
+        "eig" => eigen(square(matrix_arg(&name, args, span, scope)?, span)?, span),
+        "eigvals" => {
+            let matrix = square(matrix_arg(&name, args, span, scope)?, span)?;
+            match matrix.eigenvalues() {
+                Some(values) => Ok(Value::List(values.into_iter().map(complex).collect())),
+                None => bail!(EvaluatorError::NoConvergence(span)),
             }
-            "fract" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.fract())
+        }
+        // `diff` with two or three arguments is already parsed as a derivative
+        "diff" => {
+            check_arity(&name, &args, 2, span)?;
+            bail!(EvaluatorError::UnresolvedDerivative(span))
+        }
+        // `if` with three arguments is already parsed as piecewise
+        "if" => {
+            check_arity(&name, &args, 3, span)?;
+            let mut args = args.into_iter();
+            let condition = args.next().unwrap();
+            let value = args.next().unwrap();
+            piecewise(vec![(value, condition)], args.next(), span, scope)
+        }
+        "lu" => {
+            let matrix = square(matrix_arg(&name, args, span, scope)?, span)?;
+            let (lower, upper, permutation) = matrix.lu();
+            Ok(Value::List(vec![
+                Value::Matrix(lower),
+                Value::Matrix(upper),
+                Value::Matrix(permutation),
+            ]))
+        }
+        "qr" => {
+            let (q, r) = matrix_arg(&name, args, span, scope)?.qr();
+            Ok(Value::List(vec![Value::Matrix(q), Value::Matrix(r)]))
+        }
+        "cholesky" => match matrix_arg(&name, args, span, scope)?.cholesky() {
+            Some(lower) => Ok(Value::Matrix(lower)),
+            None => bail!(EvaluatorError::NotPositiveDefinite(span)),
+        },
+        "svd" => {
+            let (u, singular_values, v) = matrix_arg(&name, args, span, scope)?.svd();
+            Ok(Value::List(vec![
+                Value::Matrix(u),
+                Value::List(singular_values.into_iter().map(Value::Number).collect()),
+                Value::Matrix(v),
+            ]))
+        }
+        "map" | "filter" | "reduce" | "apply" => higher_order(&name, args, span, scope),
+        _ => call_function(&name, args, span, scope),
+    }
+}
+
+/// Functions taking a function, such as `map(X -> X^2, [1, 2])`. Kept apart from
+/// [`evaluate_function`] so recursive calls don't grow its stack frame.
+fn higher_order(name: &str, args: Vec<Expr>, span: Span, scope: &Scope) -> Result<Value> {
+    match name {
+        "map" | "filter" => {
+            check_arity(name, &args, 2, span)?;
+            let (function, items) = function_and_list(args, scope)?;
+            let mut results = Vec::new();
+            for item in items {
+                let result = invoke(&function, vec![item.clone()], span, scope)?;
+                if name == "map" {
+                    results.push(result);
+                } else if result.into_bool(span)? {
+                    results.push(item);
+                }
             }
-            "sqrt" => {
-                assert_eq!(args.len(), 1);
-                let arg = args.iter().next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg)?.sqrt())
+            Ok(Value::list(results))
+        }
+        "reduce" => {
+            if !(2..=3).contains(&args.len()) {
+                check_arity(name, &args, 2, span)?;
             }
-            "pow" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.powf(evaluate_expr(*arg2)?))
+            let initial = args.get(2).cloned();
+            let (function, items) = function_and_list(args, scope)?;
+            let mut items = items.into_iter();
+            let mut accumulator = match initial {
+                Some(initial) => evaluate_expr(initial, scope)?,
+                None => match items.next() {
+                    Some(first) => first,
+                    None => bail!(EvaluatorError::EmptyReduce(span)),
+                },
+            };
+            for item in items {
+                accumulator = invoke(&function, vec![accumulator, item], span, scope)?;
             }
-            "min" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.min(evaluate_expr(*arg2)?))
+            Ok(accumulator)
+        }
+        "apply" => {
+            check_arity(name, &args, 2, span)?;
+            let (function, items) = function_and_list(args, scope)?;
+            invoke(&function, items, span, scope)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Evaluates the function and the list that `map`, `filter`, `reduce` and `apply`
+/// take as their first two arguments.
+fn function_and_list(args: Vec<Expr>, scope: &Scope) -> Result<(UserFunction, Vec<Value>)> {
+    let mut args = args.into_iter();
+    let function = args.next().unwrap();
+    let function_span = function.span();
+    let function = evaluate_expr(function, scope)?.into_function(function_span)?;
+    let list = args.next().unwrap();
+    let list_span = list.span();
+    let items = match evaluate_expr(list, scope)? {
+        // A matrix is a list of its rows
+        Value::Matrix(matrix) => (0..matrix.rows())
+            .map(|row| Value::List(matrix.row(row).iter().copied().map(Value::Number).collect()))
+            .collect(),
+        value => value.into_list(list_span)?,
+    };
+    Ok((function, items))
+}
+
+/// Calls a function defined with `f(X) := ...`. The arguments are evaluated in the
+/// calling scope, the body only sees its parameters, the environment and whatever a
+/// lambda captured.
+fn call_function(name: &str, args: Vec<Expr>, span: Span, scope: &Scope) -> Result<Value> {
+    let function = match scope.get(name) {
+        Some(Value::Function(function)) => function.clone(),
+        _ => bail!(EvaluatorError::UnknownFunction(name.to_string(), span)),
+    };
+    check_arity(name, &args, function.params.len(), span)?;
+    if scope.depth >= MAX_CALL_DEPTH {
+        bail!(EvaluatorError::RecursionLimit(name.to_string(), span));
+    }
+
+    let mut locals: HashMap<String, Value> = function.captured.into_iter().collect();
+    for (param, arg) in function.params.into_iter().zip(args) {
+        locals.insert(param, evaluate_expr(arg, scope)?);
+    }
+    let inner = Scope {
+        env: scope.env,
+        locals,
+        depth: scope.depth + 1,
+    };
+    evaluate_expr(function.body, &inner)
+}
+
+/// Calls a function value with arguments that are already evaluated, as the
+/// functions taking other functions do.
+fn invoke(function: &UserFunction, args: Vec<Value>, span: Span, scope: &Scope) -> Result<Value> {
+    let name = || Value::Function(function.clone()).to_string();
+    if args.len() != function.params.len() {
+        bail!(EvaluatorError::ArgumentCount(
+            name(),
+            function.params.len(),
+            args.len(),
+            span
+        ));
+    }
+    if scope.depth >= MAX_CALL_DEPTH {
+        bail!(EvaluatorError::RecursionLimit(name(), span));
+    }
+
+    let mut locals: HashMap<String, Value> = function.captured.iter().cloned().collect();
+    locals.extend(function.params.iter().cloned().zip(args));
+    let inner = Scope {
+        env: scope.env,
+        locals,
+        depth: scope.depth + 1,
+    };
+    evaluate_expr(function.body.clone(), &inner)
+}
+
+/// Rounds every number in `value` to hide floating point noise.
+fn round_value(value: Value) -> Value {
+    match value {
+        Value::Number(val) => Value::Number(round(val, 15)),
+        Value::List(items) => Value::List(items.into_iter().map(round_value).collect()),
+        Value::Matrix(matrix) => Value::Matrix(matrix.map(|val| round(val, 15))),
+        Value::Set(set) => Value::Set(set.map(|val| round(val, 15))),
+        Value::Complex(value) => {
+            Value::Complex(Complex::new(round(value.re, 15), round(value.im, 15)))
+        }
+        value => value,
+    }
+}
+
+fn evaluate_value_expr(expr: Expr, scope: &Scope) -> Result<Value> {
+    Ok(round_value(evaluate_expr(expr, scope)?))
+}
+
+/// Runs the statements in order and returns the value of the last one. Assignments
+/// are stored in `env`. Statements parsed with a registry of their own, such as by
+/// [`parse_with_operators`](crate::parser::parse_with_operators), are evaluated this way.
+pub fn evaluate_statements(statements: Vec<Statement>, env: &mut Environment) -> Result<Value> {
+    let mut result = None;
+    for statement in statements {
+        result = Some(match statement {
+            // Stored unrounded so later statements keep the full precision
+            Statement::Assignment { name, value, .. } => {
+                let value = evaluate_expr(value, &Scope::new(env))?;
+                env.set(&name, value.clone());
+                round_value(value)
             }
-            "max" => {
-                assert_eq!(args.len(), 2);
-                let mut args = args.iter();
-                let arg1 = args.next().unwrap().to_owned();
-                let arg2 = args.next().unwrap().to_owned();
-                Ok(evaluate_expr(*arg1)?.max(evaluate_expr(*arg2)?))
+            Statement::Definition {
+                name, params, body, ..
+            } => {
+                let function = Value::Function(UserFunction {
+                    params,
+                    body,
+                    captured: Vec::new(),
+                });
+                env.set(&name, function.clone());
+                function
             }
-            name => bail!("Syntax error: no function name found"),
-        },
-        _ => todo!(),
+            Statement::Expression(expr) => evaluate_value_expr(expr, &Scope::new(env))?,
+        });
+    }
+    match result {
+        Some(value) => Ok(value),
+        None => bail!(ParserError::ExpectedOperand(Span::new(0, 0))),
+    }
+}
+
+fn expect_number(value: Value, expression: &str) -> Result<f64> {
+    match value {
+        Value::Number(val) => Ok(val),
+        value => bail!(EvaluatorError::ExpectedNumber(
+            value.kind().to_string(),
+            Span::new(0, expression.len())
+        )),
     }
 }
 
+/// Evaluates the statements in `expression` to a number. Variables are read from and
+/// assigned to the shared [`environment`](super::environment).
 pub fn evaluate(expression: &str) -> Result<f64> {
-    Ok(round(evaluate_expr(parse(expression)?)?, 15))
+    expect_number(evaluate_value(expression)?, expression)
+}
+
+/// Evaluates `expression` to a [`Value`], relations such as `1 < 2` give booleans and
+/// `[1, 2] * 2` gives a list.
+pub fn evaluate_value(expression: &str) -> Result<Value> {
+    // Locked for the whole evaluation so concurrent assignments aren't lost
+    evaluate_with(expression, &mut environment())
+}
+
+/// Evaluates `expression` with its own set of variables instead of the shared one.
+pub fn evaluate_with(expression: &str, env: &mut Environment) -> Result<Value> {
+    evaluate_with_config(expression, env, &parse_config())
+}
+
+/// Evaluates `expression` like [`evaluate_with`] with its own separators instead of
+/// the global ones, such as `max(1,5; 2)` for a locale with decimal commas.
+pub fn evaluate_with_config(
+    expression: &str,
+    env: &mut Environment,
+    config: &ParseConfig,
+) -> Result<Value> {
+    evaluate_statements(parse_statements_with_config(expression, config)?, env)
+}
+
+pub fn evaluate_latex(expression: &str) -> Result<f64> {
+    let expr = parse_latex(expression)?;
+    let env = environment().clone();
+    expect_number(evaluate_value_expr(expr, &Scope::new(&env))?, expression)
 }
@@ -1,3 +1,11 @@
+mod environment;
 mod evaluator;
+mod value;
 
-pub use evaluator::evaluate;
+pub use crate::math::{Complex, Interval, Matrix, Set};
+pub use environment::{environment, Environment};
+pub use evaluator::{
+    evaluate, evaluate_latex, evaluate_statements, evaluate_value, evaluate_with,
+    evaluate_with_config,
+};
+pub use value::{UserFunction, Value};
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+use crate::error::EvaluatorError;
+use crate::math::{Complex, Matrix, Set};
+use crate::parser::{Expr, Span};
+
+/// A function defined with `f(X) := X^2 + 1`, or a lambda such as `X -> X^2 + 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    pub body: Expr,
+    /// Parameters of the enclosing functions that were visible where a lambda was
+    /// written. Empty for definitions.
+    pub captured: Vec<(String, Value)>,
+}
+
+/// Result of evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// Result of a relation such as `1 < 2`.
+    Bool(bool),
+    Function(UserFunction),
+    List(Vec<Value>),
+    Matrix(Matrix),
+    /// Only produced by functions such as `eigvals`, arithmetic stays real.
+    Complex(Complex),
+    /// Intervals and finite sets such as `[0, 1) ∪ {2}`.
+    Set(Set),
+}
+
+impl Value {
+    /// Describes the kind of value for error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Bool(_) => "a boolean",
+            Value::Function(_) => "a function",
+            Value::List(_) => "a list",
+            Value::Matrix(_) => "a matrix",
+            Value::Complex(_) => "a complex number",
+            Value::Set(_) => "a set",
+        }
+    }
+
+    /// Builds a list, or a matrix when every item is a list of numbers and all of
+    /// them have the same length, as in `[[1, 2], [3, 4]]`.
+    pub fn list(items: Vec<Value>) -> Value {
+        let rows: Option<Vec<Vec<f64>>> = items
+            .iter()
+            .map(|item| match item {
+                Value::List(row) => row
+                    .iter()
+                    .map(|value| match value {
+                        Value::Number(val) => Some(*val),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            })
+            .collect();
+        match rows {
+            Some(rows)
+                if !rows.is_empty()
+                    && !rows[0].is_empty()
+                    && rows.iter().all(|row| row.len() == rows[0].len()) =>
+            {
+                Value::Matrix(Matrix::from_rows(rows))
+            }
+            _ => Value::List(items),
+        }
+    }
+
+    pub fn into_number(self, span: Span) -> Result<f64> {
+        match self {
+            Value::Number(val) => Ok(val),
+            value => bail!(EvaluatorError::ExpectedNumber(
+                value.kind().to_string(),
+                span
+            )),
+        }
+    }
+
+    pub fn into_bool(self, span: Span) -> Result<bool> {
+        match self {
+            Value::Bool(val) => Ok(val),
+            value => bail!(EvaluatorError::ExpectedCondition(
+                value.kind().to_string(),
+                span
+            )),
+        }
+    }
+
+    pub fn into_matrix(self, span: Span) -> Result<Matrix> {
+        match self {
+            Value::Matrix(matrix) => Ok(matrix),
+            value => bail!(EvaluatorError::ExpectedMatrix(
+                value.kind().to_string(),
+                span
+            )),
+        }
+    }
+
+    pub fn into_function(self, span: Span) -> Result<UserFunction> {
+        match self {
+            Value::Function(function) => Ok(function),
+            value => bail!(EvaluatorError::ExpectedFunction(
+                value.kind().to_string(),
+                span
+            )),
+        }
+    }
+
+    /// A list of numbers is the finite set of its items.
+    pub fn into_set(self, span: Span) -> Result<Set> {
+        match self {
+            Value::Set(set) => Ok(set),
+            Value::List(items) => {
+                let points = items
+                    .into_iter()
+                    .map(|item| item.into_number(span))
+                    .collect::<Result<Vec<f64>>>()?;
+                Ok(Set::from_points(&points))
+            }
+            value => bail!(EvaluatorError::ExpectedSet(value.kind().to_string(), span)),
+        }
+    }
+
+    pub fn into_list(self, span: Span) -> Result<Vec<Value>> {
+        match self {
+            Value::List(items) => Ok(items),
+            value => bail!(EvaluatorError::ExpectedList(value.kind().to_string(), span)),
+        }
+    }
+
+    /// Applies `function` to a number, or to every number of a list or matrix.
+    pub fn map(self, span: Span, function: impl Fn(f64) -> f64 + Copy) -> Result<Value> {
+        match self {
+            Value::Matrix(matrix) => Ok(Value::Matrix(matrix.map(function))),
+            Value::List(items) => Ok(Value::List(
+                items
+                    .into_iter()
+                    .map(|item| item.map(span, function))
+                    .collect::<Result<Vec<Value>>>()?,
+            )),
+            value => Ok(Value::Number(function(value.into_number(span)?))),
+        }
+    }
+
+    /// Applies `function` element-wise. A number is paired with every element of a
+    /// list or matrix, while two lists or matrices need to be of the same shape.
+    pub fn zip(
+        self,
+        other: Value,
+        span: Span,
+        function: impl Fn(f64, f64) -> f64 + Copy,
+    ) -> Result<Value> {
+        match (self, other) {
+            (Value::Matrix(lhs), Value::Matrix(rhs)) => match lhs.zip(&rhs, function) {
+                Some(matrix) => Ok(Value::Matrix(matrix)),
+                None => bail!(EvaluatorError::ShapeMismatch(
+                    lhs.shape(),
+                    rhs.shape(),
+                    span
+                )),
+            },
+            (Value::Matrix(lhs), rhs) => {
+                let rhs = rhs.into_number(span)?;
+                Ok(Value::Matrix(lhs.map(|lhs| function(lhs, rhs))))
+            }
+            (lhs, Value::Matrix(rhs)) => {
+                let lhs = lhs.into_number(span)?;
+                Ok(Value::Matrix(rhs.map(|rhs| function(lhs, rhs))))
+            }
+            (Value::List(lhs), Value::List(rhs)) => {
+                if lhs.len() != rhs.len() {
+                    bail!(EvaluatorError::LengthMismatch(lhs.len(), rhs.len(), span));
+                }
+                Ok(Value::List(
+                    lhs.into_iter()
+                        .zip(rhs)
+                        .map(|(lhs, rhs)| lhs.zip(rhs, span, function))
+                        .collect::<Result<Vec<Value>>>()?,
+                ))
+            }
+            (Value::List(lhs), rhs) => Ok(Value::List(
+                lhs.into_iter()
+                    .map(|lhs| lhs.zip(rhs.clone(), span, function))
+                    .collect::<Result<Vec<Value>>>()?,
+            )),
+            (lhs, Value::List(rhs)) => Ok(Value::List(
+                rhs.into_iter()
+                    .map(|rhs| lhs.clone().zip(rhs, span, function))
+                    .collect::<Result<Vec<Value>>>()?,
+            )),
+            (lhs, rhs) => Ok(Value::Number(function(
+                lhs.into_number(span)?,
+                rhs.into_number(span)?,
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(val) => write!(f, "{val}"),
+            Value::Bool(val) => write!(f, "{val}"),
+            Value::Function(function) => {
+                write!(f, "({}) -> {}", function.params.join(", "), function.body)
+            }
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Matrix(matrix) => write!(f, "{matrix}"),
+            Value::Complex(complex) => write!(f, "{complex}"),
+            Value::Set(set) => write!(f, "{set}"),
+        }
+    }
+}
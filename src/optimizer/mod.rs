@@ -1,11 +1,40 @@
-use crate::parser::{Expr, Op, Optimize};
+use crate::math::step_count;
+use crate::parser::{merge_factors, Expr, Op, Optimize, Series};
+
+/// Whether a condition holds, when it is `true`, `false` or only compares numbers.
+fn constant_condition(condition: &Expr) -> Option<bool> {
+    if let Expr::Bool(value, _) = condition {
+        return Some(*value);
+    }
+    let Expr::Relation {
+        operands,
+        relations,
+        ..
+    } = condition
+    else {
+        return None;
+    };
+    let numbers = operands
+        .iter()
+        .map(|operand| match operand {
+            Expr::Number(value, _) => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    Some(
+        relations
+            .iter()
+            .zip(numbers.windows(2))
+            .all(|(relation, pair)| relation.holds(pair[0], pair[1])),
+    )
+}
 
 impl Optimize for Expr {
     fn optimize_expression(self) -> Expr {
         let mut old = self.clone();
         let mut latest = self.optimize_node();
 
-        while old != latest {
+        while !old.same_shape(&latest) {
             old = latest.clone();
             latest = latest.optimize_node();
         }
@@ -15,35 +44,35 @@ impl Optimize for Expr {
 
     fn optimize_node(&self) -> Expr {
         match self {
-            Expr::UnaryMinus(inner) => {
+            Expr::UnaryMinus(inner, span) => {
                 let inner = *inner.to_owned();
                 // -(-a) => a
-                if let Expr::UnaryMinus(inner_inner) = inner {
+                if let Expr::UnaryMinus(inner_inner, _) = inner {
                     return *inner_inner;
                 }
 
                 // -0 = 0
-                if let Expr::Number(inner_n) = inner {
+                if let Expr::Number(inner_n, _) = inner {
                     if inner_n == 0.0 {
-                        return Expr::Number(0.0);
+                        return Expr::Number(0.0, *span);
                     }
                 }
 
-                Expr::UnaryMinus(Box::new(inner.optimize_node()))
+                Expr::UnaryMinus(Box::new(inner.optimize_node()), *span)
             }
-            Expr::BinOp { lhs, op, rhs } => {
+            Expr::BinOp { lhs, op, rhs, span } => {
                 let optimized_lhs = lhs.optimize_node();
                 let optimized_rhs = rhs.optimize_node();
 
                 // 0 + a = a
-                if let (Expr::Number(num), Op::Add) = (&optimized_lhs, &op) {
+                if let (Expr::Number(num, _), Op::Add) = (&optimized_lhs, &op) {
                     if num == &0.0 {
                         return optimized_rhs;
                     }
                 }
 
                 // a + 0 = a
-                if let (Expr::Number(num), Op::Add) = (&optimized_rhs, &op) {
+                if let (Expr::Number(num, _), Op::Add) = (&optimized_rhs, &op) {
                     if num == &0.0 {
                         return optimized_lhs;
                     }
@@ -51,56 +80,56 @@ impl Optimize for Expr {
 
                 // a - a = 0
                 if let Op::Subtract = op {
-                    if optimized_lhs == optimized_rhs {
-                        return Expr::Number(0.0);
+                    if optimized_lhs.same_shape(&optimized_rhs) {
+                        return Expr::Number(0.0, *span);
                     }
                 }
 
                 // 0 - a = a
-                if let (Expr::Number(num), Op::Subtract) = (&optimized_lhs, &op) {
+                if let (Expr::Number(num, _), Op::Subtract) = (&optimized_lhs, &op) {
                     if num == &0.0 {
                         return optimized_rhs;
                     }
                 }
 
                 // a - 0 = a
-                if let (Expr::Number(num), Op::Subtract) = (&optimized_rhs, &op) {
+                if let (Expr::Number(num, _), Op::Subtract) = (&optimized_rhs, &op) {
                     if num == &0.0 {
                         return optimized_lhs;
                     }
                 }
 
                 // 1 * a = a
-                if let (Expr::Number(num), Op::Multiply) = (&optimized_lhs, &op) {
+                if let (Expr::Number(num, _), Op::Multiply) = (&optimized_lhs, &op) {
                     if num == &1.0 {
                         return optimized_rhs;
                     }
                 }
 
                 // a * 1 = a
-                if let (Expr::Number(num), Op::Multiply) = (&optimized_rhs, &op) {
+                if let (Expr::Number(num, _), Op::Multiply) = (&optimized_rhs, &op) {
                     if num == &1.0 {
                         return optimized_lhs;
                     }
                 }
 
                 // 0 * a = 0
-                if let (Expr::Number(num), Op::Multiply) = (&optimized_lhs, &op) {
+                if let (Expr::Number(num, _), Op::Multiply) = (&optimized_lhs, &op) {
                     if num == &0.0 {
-                        return Expr::Number(0.0);
+                        return Expr::Number(0.0, *span);
                     }
                 }
 
                 // a * 0 = 0
-                if let (Expr::Number(num), Op::Multiply) = (&optimized_rhs, &op) {
+                if let (Expr::Number(num, _), Op::Multiply) = (&optimized_rhs, &op) {
                     if num == &0.0 {
-                        return Expr::Number(0.0);
+                        return Expr::Number(0.0, *span);
                     }
                 }
 
                 // a * a = a^2
                 if let Op::Multiply = op {
-                    if optimized_lhs == optimized_rhs {
+                    if optimized_lhs.same_shape(&optimized_rhs) {
                         if let (Expr::Monomial { .. }, Expr::Monomial { .. }) =
                             (&optimized_lhs, &optimized_rhs)
                         {
@@ -108,7 +137,8 @@ impl Optimize for Expr {
                             return Expr::BinOp {
                                 lhs: Box::new(optimized_lhs),
                                 op: Op::Power,
-                                rhs: Box::new(Expr::Number(2.0)),
+                                rhs: Box::new(Expr::Number(2.0, *span)),
+                                span: *span,
                             };
                         }
                     }
@@ -121,16 +151,18 @@ impl Optimize for Expr {
                             lhs: left_lhs,
                             op: left_operator,
                             rhs: left_rhs,
+                            ..
                         },
                         Expr::BinOp {
                             lhs: right_lhs,
                             op: right_operator,
                             rhs: right_rhs,
+                            ..
                         },
                     ) = (&optimized_lhs, &optimized_rhs)
                     {
                         if let (Op::Power, Op::Power) = (left_operator, right_operator) {
-                            if left_lhs == right_lhs {
+                            if left_lhs.same_shape(right_lhs) {
                                 return Expr::BinOp {
                                     lhs: Box::new(*left_lhs.to_owned()),
                                     op: Op::Power,
@@ -138,7 +170,9 @@ impl Optimize for Expr {
                                         lhs: left_rhs.to_owned(),
                                         op: Op::Add,
                                         rhs: right_rhs.to_owned(),
+                                        span: *span,
                                     }),
+                                    span: *span,
                                 };
                             }
                         }
@@ -146,31 +180,33 @@ impl Optimize for Expr {
                 }
 
                 // a^1 = a
-                if let (Expr::Number(n), Op::Power) = (&optimized_rhs, op) {
+                if let (Expr::Number(n, _), Op::Power) = (&optimized_rhs, op) {
                     if n == &1.0 {
                         return optimized_lhs;
                     }
                 }
 
                 // a^-n = 1/(a^n)
-                if let (Expr::UnaryMinus(inner), Op::Power) = (&optimized_rhs, op) {
+                if let (Expr::UnaryMinus(inner, _), Op::Power) = (&optimized_rhs, op) {
                     let inner = *inner.to_owned();
 
-                    if let Expr::Number(n) = inner {
+                    if let Expr::Number(n, _) = inner {
                         return Expr::BinOp {
-                            lhs: Box::new(Expr::Number(1.0)),
+                            lhs: Box::new(Expr::Number(1.0, *span)),
                             op: Op::Divide,
                             rhs: Box::new(Expr::BinOp {
                                 lhs: Box::new(optimized_lhs),
                                 op: Op::Power,
-                                rhs: Box::new(Expr::Number(n)),
+                                rhs: Box::new(Expr::Number(n, *span)),
+                                span: *span,
                             }),
+                            span: *span,
                         };
                     }
                 }
 
                 // a / 1 = a
-                if let (Expr::Number(num), Op::Divide) = (&optimized_rhs, &op) {
+                if let (Expr::Number(num, _), Op::Divide) = (&optimized_rhs, &op) {
                     if num == &1.0 {
                         return optimized_lhs;
                     }
@@ -178,55 +214,62 @@ impl Optimize for Expr {
 
                 // a / a = 1
                 if let Op::Divide = op {
-                    if optimized_lhs == optimized_rhs {
-                        return Expr::Number(1.0);
+                    if optimized_lhs.same_shape(&optimized_rhs) {
+                        return Expr::Number(1.0, *span);
                     }
                 }
 
-                // aX^b + cX^d = (a+b)X^(b+d)
+                // aX^bY^c + dX^bY^c = (a+d)X^bY^c
                 if let (
                     Expr::Monomial {
                         coefficient: left_coefficient,
-                        variable: left_variable,
-                        exponent: left_exponent,
+                        factors: left_factors,
+                        ..
                     },
                     Expr::Monomial {
                         coefficient: right_coefficient,
-                        variable: right_variable,
-                        exponent: right_exponent,
+                        factors: right_factors,
+                        ..
                     },
                     Op::Add,
                 ) = (&optimized_lhs, &optimized_rhs, op)
                 {
-                    if left_variable == right_variable && left_exponent == right_exponent {
+                    if left_factors == right_factors {
                         return Expr::Monomial {
                             coefficient: left_coefficient + right_coefficient,
-                            variable: left_variable.to_owned(),
-                            exponent: left_exponent.to_owned(),
+                            factors: left_factors.to_owned(),
+                            span: *span,
                         };
                     }
                 }
 
-                // aX^b * cX^d = (a*b)X^(b*d)
+                // aX^b * cX^dY^e = (a*c)X^(b+d)Y^e, and a number once no variable is left
                 if let (
                     Expr::Monomial {
                         coefficient: left_coefficient,
-                        variable: left_variable,
-                        exponent: left_exponent,
+                        factors: left_factors,
+                        ..
                     },
                     Expr::Monomial {
                         coefficient: right_coefficient,
-                        variable: right_variable,
-                        exponent: right_exponent,
+                        factors: right_factors,
+                        ..
                     },
                     Op::Multiply,
                 ) = (&optimized_lhs, &optimized_rhs, op)
                 {
-                    if left_variable == right_variable {
+                    if let Some(mut factors) =
+                        merge_factors([left_factors.as_slice(), right_factors].concat())
+                    {
+                        let coefficient = left_coefficient * right_coefficient;
+                        factors.retain(|(_, exponent)| !exponent.is_zero());
+                        if factors.is_empty() {
+                            return Expr::Number(coefficient, *span);
+                        }
                         return Expr::Monomial {
-                            coefficient: left_coefficient * right_coefficient,
-                            variable: left_variable.to_owned(),
-                            exponent: left_exponent + right_exponent,
+                            coefficient,
+                            factors,
+                            span: *span,
                         };
                     }
                 }
@@ -235,11 +278,203 @@ impl Optimize for Expr {
                     lhs: Box::new(optimized_lhs),
                     op: *op,
                     rhs: Box::new(optimized_rhs),
+                    span: *span,
+                }
+            }
+            Expr::UnaryOp { op, operand, span } => Expr::UnaryOp {
+                op: *op,
+                operand: Box::new(operand.optimize_node()),
+                span: *span,
+            },
+            Expr::Relation {
+                operands,
+                relations,
+                span,
+            } => Expr::Relation {
+                operands: operands
+                    .iter()
+                    .map(|operand| operand.optimize_node())
+                    .collect(),
+                relations: relations.clone(),
+                span: *span,
+            },
+            Expr::List { items, span } => Expr::List {
+                items: items.iter().map(|item| item.optimize_node()).collect(),
+                span: *span,
+            },
+            Expr::Index {
+                target,
+                indices,
+                span,
+            } => Expr::Index {
+                target: Box::new(target.optimize_node()),
+                indices: indices.iter().map(|index| index.optimize_node()).collect(),
+                span: *span,
+            },
+            Expr::Series {
+                kind,
+                variable,
+                lower,
+                upper,
+                body,
+                span,
+            } => {
+                let lower = lower.optimize_node();
+                let upper = upper.optimize_node();
+                let body = body.optimize_node();
+
+                // A constant body over numeric bounds is repeated addition or
+                // multiplication, sum(k, 1, 4, c) => c*4 and prod(k, 1, 4, c) => c^4.
+                // Bounds that evaluation rejects are left for it to report.
+                if let (Expr::Number(lower, _), Expr::Number(upper, _), Expr::Number(..)) =
+                    (&lower, &upper, &body)
+                {
+                    let whole = lower.fract() == 0.0 && upper.fract() == 0.0;
+                    if let Some(count) = step_count(*lower, *upper).filter(|_| whole) {
+                        let count = Expr::Number(count as f64, *span);
+                        return Expr::BinOp {
+                            lhs: Box::new(body),
+                            op: match kind {
+                                Series::Sum => Op::Multiply,
+                                Series::Product => Op::Power,
+                            },
+                            rhs: Box::new(count),
+                            span: *span,
+                        };
+                    }
+                }
+
+                Expr::Series {
+                    kind: *kind,
+                    variable: variable.clone(),
+                    lower: Box::new(lower),
+                    upper: Box::new(upper),
+                    body: Box::new(body),
+                    span: *span,
+                }
+            }
+            Expr::Lambda { params, body, span } => Expr::Lambda {
+                params: params.clone(),
+                body: Box::new(body.optimize_node()),
+                span: *span,
+            },
+            Expr::Piecewise {
+                cases,
+                otherwise,
+                span,
+            } => {
+                let mut optimized_cases = Vec::new();
+                let mut optimized_otherwise = otherwise
+                    .as_ref()
+                    .map(|otherwise| Box::new(otherwise.optimize_node()));
+                for (value, condition) in cases {
+                    let value = value.optimize_node();
+                    let condition = condition.optimize_node();
+                    match constant_condition(&condition) {
+                        // Cases after one that always holds are never reached
+                        Some(true) => {
+                            optimized_otherwise = Some(Box::new(value));
+                            break;
+                        }
+                        Some(false) => {}
+                        None => optimized_cases.push((value, condition)),
+                    }
+                }
+
+                match (optimized_cases.is_empty(), optimized_otherwise) {
+                    (true, Some(otherwise)) => *otherwise,
+                    // No case can hold, which is kept as is to fail when evaluated
+                    (true, None) => self.clone(),
+                    (false, otherwise) => Expr::Piecewise {
+                        cases: optimized_cases,
+                        otherwise,
+                        span: *span,
+                    },
+                }
+            }
+            Expr::Operator {
+                symbol,
+                fixity,
+                operands,
+                function,
+                span,
+            } => Expr::Operator {
+                symbol: symbol.clone(),
+                fixity: *fixity,
+                operands: operands
+                    .iter()
+                    .map(|operand| operand.optimize_node())
+                    .collect(),
+                function: function.clone(),
+                span: *span,
+            },
+            Expr::Number(n, span) => Expr::Number(*n, *span),
+            Expr::Monomial { .. } => self.clone(),
+            Expr::Constant { .. } => self.clone(),
+            Expr::Bool(..) => self.clone(),
+            // not (1 < 2) => false
+            Expr::Not(operand, span) => {
+                let operand = operand.optimize_node();
+                match constant_condition(&operand) {
+                    Some(value) => Expr::Bool(!value, *span),
+                    None => Expr::Not(Box::new(operand), *span),
+                }
+            }
+            Expr::Logic { lhs, op, rhs, span } => {
+                let lhs = lhs.optimize_node();
+                let rhs = rhs.optimize_node();
+                match (constant_condition(&lhs), constant_condition(&rhs)) {
+                    (Some(lhs), Some(rhs)) => Expr::Bool(op.apply(lhs, rhs), *span),
+                    _ => Expr::Logic {
+                        lhs: Box::new(lhs),
+                        op: *op,
+                        rhs: Box::new(rhs),
+                        span: *span,
+                    },
                 }
             }
-            Expr::Number(n) => Expr::Number(*n),
-            Expr::Monomial { .. } => return self.clone(),
-            token => todo!("Optimizing for '{token:?}' not implemented yet!"),
+            Expr::Interval {
+                lower,
+                upper,
+                lower_closed,
+                upper_closed,
+                span,
+            } => Expr::Interval {
+                lower: Box::new(lower.optimize_node()),
+                upper: Box::new(upper.optimize_node()),
+                lower_closed: *lower_closed,
+                upper_closed: *upper_closed,
+                span: *span,
+            },
+            Expr::Set { items, span } => Expr::Set {
+                items: items.iter().map(|item| item.optimize_node()).collect(),
+                span: *span,
+            },
+            Expr::SetOp { lhs, op, rhs, span } => Expr::SetOp {
+                lhs: Box::new(lhs.optimize_node()),
+                op: *op,
+                rhs: Box::new(rhs.optimize_node()),
+                span: *span,
+            },
+            // `f'(X)` is kept as written
+            Expr::Derivative { variable: None, .. } => self.clone(),
+            Expr::Derivative {
+                expr,
+                variable,
+                order,
+                span,
+            } => Expr::Derivative {
+                expr: Box::new(expr.optimize_node()),
+                variable: variable.clone(),
+                order: *order,
+                span: *span,
+            },
+            Expr::Function { name, args, span } => Expr::Function {
+                name: name.clone(),
+                args: args.iter().map(|arg| arg.optimize_node()).collect(),
+                span: *span,
+            },
+            Expr::Error(_) => self.clone(),
         }
     }
 
@@ -0,0 +1,115 @@
+use std::borrow::Cow;
+use std::sync::RwLock;
+
+use anyhow::{bail, Result};
+
+use crate::error::ConfigError;
+
+/// Separators of numbers and arguments, for locales that write `3,5` for 3.5 and
+/// `max(1; 2)` for the maximum. The default is the syntax of the readme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseConfig {
+    decimal: char,
+    argument: char,
+    thousands: Option<char>,
+}
+
+impl Default for ParseConfig {
+    fn default() -> ParseConfig {
+        ParseConfig {
+            decimal: '.',
+            argument: ',',
+            thousands: None,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// The decimal separator is `.` or `,` and the argument separator `,` or `;`.
+    /// Digits can be grouped by thousands with `.`, `,`, `'` or a space, as in
+    /// `1.000.000,5`. No character can be two separators at once.
+    pub fn new(decimal: char, argument: char, thousands: Option<char>) -> Result<ParseConfig> {
+        if !matches!(decimal, '.' | ',') {
+            bail!(ConfigError::InvalidSeparator(decimal));
+        }
+        if !matches!(argument, ',' | ';') {
+            bail!(ConfigError::InvalidSeparator(argument));
+        }
+        if let Some(thousands) = thousands.filter(|c| !matches!(c, '.' | ',' | '\'' | ' ')) {
+            bail!(ConfigError::InvalidSeparator(thousands));
+        }
+        for (separator, other) in [
+            (decimal, Some(argument)),
+            (decimal, thousands),
+            (argument, thousands),
+        ] {
+            if Some(separator) == other {
+                bail!(ConfigError::DuplicateSeparator(separator));
+            }
+        }
+
+        Ok(ParseConfig {
+            decimal,
+            argument,
+            thousands,
+        })
+    }
+
+    /// Rewrites `expression` into the default syntax, so the grammar only knows one.
+    /// Separators are swapped for single ASCII characters, which keeps the spans of
+    /// the result valid for `expression`.
+    ///
+    /// A decimal separator has to be between digits and thousands are groups of
+    /// exactly three digits. Argument separators only count inside brackets, so `;`
+    /// still separates statements. Separators of the default syntax that have no
+    /// other meaning keep working.
+    pub(crate) fn normalize<'a>(&self, expression: &'a str) -> Cow<'a, str> {
+        if *self == ParseConfig::default() {
+            return Cow::Borrowed(expression);
+        }
+
+        let bytes = expression.as_bytes();
+        let digit = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_digit);
+        // Multi-byte characters only hold bytes above ASCII, so they are never touched
+        let mut normalized = bytes.to_vec();
+        let mut depth = 0usize;
+        for (index, &byte) in bytes.iter().enumerate() {
+            match byte {
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            let separator = Some(byte as char);
+            let after_digit = index > 0 && digit(index - 1);
+            // `0,(3)` repeats like `0.(3)`
+            let fraction =
+                digit(index + 1) || bytes.get(index + 1) == Some(&b'(') && digit(index + 2);
+            let group = (1..=3).all(|offset| digit(index + offset)) && !digit(index + 4);
+            if separator == Some(self.decimal) && after_digit && fraction {
+                normalized[index] = b'.';
+            } else if separator == self.thousands && after_digit && group {
+                normalized[index] = b'_';
+            } else if separator == Some(self.argument) && depth > 0 {
+                normalized[index] = b',';
+            }
+        }
+        Cow::Owned(String::from_utf8(normalized).unwrap())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIG: RwLock<ParseConfig> = RwLock::new(ParseConfig::default());
+}
+
+/// Sets the separators used by [`parse`](super::parse) and everything built on it, the
+/// default of the wasm build. Functions such as
+/// [`parse_with_config`](super::parse_with_config) take a config of their own instead.
+pub fn set_parse_config(config: ParseConfig) {
+    *CONFIG.write().unwrap() = config;
+}
+
+/// The separators used by [`parse`](super::parse).
+pub fn parse_config() -> ParseConfig {
+    *CONFIG.read().unwrap()
+}
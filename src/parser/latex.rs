@@ -0,0 +1,322 @@
+use std::iter::Peekable;
+
+use anyhow::{bail, Result};
+use pest::iterators::{Pair, Pairs};
+use pest::Parser;
+use phf::phf_set;
+
+use crate::error::ParserError;
+
+use super::config::parse_config;
+use super::number::parse_number;
+use super::operators::operators;
+use super::parser::{
+    build_expr, build_monomial, push_symbols, syntax_error, Item,
+};
+use super::{resolve_name, Expr, Name, Op, OperatorRegistry, ParseConfig, Span};
+
+#[derive(pest_derive::Parser)]
+#[grammar = "grammar/latex.pest"]
+struct LatexParser;
+
+/// Commands naming a letter, which are variables unless they are constants such as
+/// `\pi`.
+static GREEK_LETTERS: phf::Set<&'static str> = phf_set! {
+    "alpha", "beta", "gamma", "delta", "epsilon", "varepsilon", "zeta", "eta", "theta",
+    "vartheta", "iota", "kappa", "lambda", "mu", "nu", "xi", "pi", "varpi", "rho", "varrho",
+    "sigma", "varsigma", "tau", "upsilon", "phi", "varphi", "chi", "psi", "omega", "Gamma",
+    "Delta", "Theta", "Lambda", "Xi", "Pi", "Sigma", "Upsilon", "Phi", "Psi", "Omega",
+};
+
+/// Commands naming a function, called like `\sin x` or `\ln\left(x\right)`.
+static FUNCTION_COMMANDS: phf::Set<&'static str> = phf_set! {
+    "sin", "cos", "tan", "ln", "log", "exp", "min", "max", "det",
+};
+
+fn parse_name(name: Pair<Rule>) -> Result<(String, Span)> {
+    let span = Span::from(name.as_span());
+    let mut text = String::new();
+    for pair in name.into_inner() {
+        match pair.as_rule() {
+            Rule::operatorname => text.push_str(pair.into_inner().next().unwrap().as_str()),
+            Rule::command => match &pair.as_str()[1..] {
+                "infty" => text.push_str("inf"),
+                command if GREEK_LETTERS.contains(command) => text.push_str(command),
+                command if FUNCTION_COMMANDS.contains(command) => text.push_str(command),
+                _ => bail!(ParserError::UnknownCommand(
+                    pair.as_str().to_string(),
+                    Span::from(pair.as_span())
+                )),
+            },
+            Rule::index => {
+                text.push('_');
+                text.push_str(pair.as_str().trim_start_matches('{').trim_end_matches('}'));
+            }
+            _ => text.push_str(pair.as_str()),
+        }
+    }
+    Ok((text, span))
+}
+
+/// Parses the argument of a command such as `\frac`, either a braced group or a
+/// single digit or name.
+fn parse_argument(argument: Pair<Rule>, registry: &OperatorRegistry) -> Result<Expr> {
+    let span = Span::from(argument.as_span());
+    match argument.as_rule() {
+        Rule::braced => {
+            let mut expr = parse_expr(argument.into_inner().next().unwrap(), registry)?;
+            *expr.span_mut() = span;
+            Ok(expr)
+        }
+        Rule::digit => Ok(Expr::Number(parse_number(argument.as_str(), span)?, span)),
+        Rule::name => build_monomial(None, parse_name(argument)?, None, span),
+        rule => bail!(ParserError::InvalidToken(format!("{:?}", rule), span)),
+    }
+}
+
+fn parse_group(group: Pair<Rule>, registry: &OperatorRegistry) -> Result<Vec<Expr>> {
+    group
+        .into_inner()
+        .map(|expr| parse_expr(expr, registry))
+        .collect()
+}
+
+/// Parses the arguments following a function name. `\sin(x)` and `\max\left(a, b\right)`
+/// take their arguments from the parentheses, `\sin x` and `\sin 2x` from the next term.
+/// An argument taken from the next term stops before another function, so `\sin30\cos60`
+/// is `sin(30)` times `cos(60)`.
+fn parse_function(
+    (name, name_span): (String, Span),
+    pairs: &mut Peekable<Pairs<Rule>>,
+    registry: &OperatorRegistry,
+) -> Result<Expr> {
+    let args = match pairs.peek().map(|pair| pair.as_rule()) {
+        Some(Rule::group) => parse_group(pairs.next().unwrap(), registry)?,
+        Some(Rule::monomial) => {
+            let monomial = MonomialParts::parse(pairs.next().unwrap())?;
+            match monomial.coefficient {
+                Some((coefficient, coefficient_span)) if monomial.is_function() => {
+                    let call = Expr::Function {
+                        name,
+                        args: vec![Expr::Number(coefficient, coefficient_span)],
+                        span: name_span.merge(coefficient_span),
+                    };
+                    let rest = MonomialParts {
+                        coefficient: None,
+                        ..monomial
+                    }
+                    .build(pairs, registry)?;
+                    return Ok(Expr::BinOp {
+                        span: call.span().merge(rest.span()),
+                        lhs: Box::new(call),
+                        op: Op::Multiply,
+                        rhs: Box::new(rest),
+                    });
+                }
+                _ => vec![monomial.build(pairs, registry)?],
+            }
+        }
+        Some(Rule::number | Rule::fraction | Rule::root | Rule::braced) => {
+            vec![parse_operand(pairs.next().unwrap(), pairs, registry)?]
+        }
+        _ => bail!(ParserError::MissingArguments(name, name_span)),
+    };
+    let span = args
+        .iter()
+        .fold(name_span, |span, arg| span.merge(arg.span()));
+    Ok(Expr::Function { name, args, span })
+}
+
+/// The coefficient, name and exponent of a `monomial` pair.
+struct MonomialParts {
+    coefficient: Option<(f64, Span)>,
+    name: (String, Span),
+    exponent: Option<(f64, Span)>,
+}
+
+impl MonomialParts {
+    fn parse(monomial: Pair<Rule>) -> Result<MonomialParts> {
+        let mut coefficient = None;
+        let mut exponent = None;
+        let mut name = None;
+        for pair in monomial.into_inner() {
+            let pair_span = Span::from(pair.as_span());
+            match pair.as_rule() {
+                Rule::coefficient => {
+                    coefficient = Some((parse_number(pair.as_str(), pair_span)?, pair_span))
+                }
+                Rule::name => name = Some(parse_name(pair)?),
+                Rule::exponent => {
+                    let number = pair.into_inner().next().unwrap();
+                    exponent = Some((parse_number(number.as_str(), pair_span)?, pair_span));
+                }
+                rule => bail!(ParserError::InvalidToken(format!("{:?}", rule), pair_span)),
+            }
+        }
+        Ok(MonomialParts {
+            coefficient,
+            name: name.unwrap(),
+            exponent,
+        })
+    }
+
+    fn is_function(&self) -> bool {
+        matches!(resolve_name(&self.name.0), Name::Function)
+    }
+
+    fn span(&self) -> Span {
+        let start = self.coefficient.map_or(self.name.1, |(_, span)| span);
+        let end = self.exponent.map_or(self.name.1, |(_, span)| span);
+        start.merge(end)
+    }
+
+    /// Builds the monomial, or the call of a function name taking its arguments from
+    /// the following terms.
+    fn build(self, pairs: &mut Peekable<Pairs<Rule>>, registry: &OperatorRegistry) -> Result<Expr> {
+        let span = self.span();
+        if !self.is_function() {
+            return build_monomial(self.coefficient, self.name, self.exponent, span);
+        }
+
+        // `2\sin^2 x` is two times the square of the sine
+        let mut expr = parse_function(self.name, pairs, registry)?;
+        if let Some((exponent, exponent_span)) = self.exponent {
+            let span = expr.span().merge(exponent_span);
+            expr = Expr::BinOp {
+                lhs: Box::new(expr),
+                op: Op::Power,
+                rhs: Box::new(Expr::Number(exponent, exponent_span)),
+                span,
+            };
+        }
+        if let Some((coefficient, coefficient_span)) = self.coefficient {
+            let span = expr.span().merge(coefficient_span);
+            expr = Expr::BinOp {
+                lhs: Box::new(Expr::Number(coefficient, coefficient_span)),
+                op: Op::Multiply,
+                rhs: Box::new(expr),
+                span,
+            };
+        }
+        Ok(expr)
+    }
+}
+
+fn parse_operand(
+    pair: Pair<Rule>,
+    pairs: &mut Peekable<Pairs<Rule>>,
+    registry: &OperatorRegistry,
+) -> Result<Expr> {
+    let span = Span::from(pair.as_span());
+    match pair.as_rule() {
+        Rule::number => Ok(Expr::Number(parse_number(pair.as_str(), span)?, span)),
+        Rule::monomial => MonomialParts::parse(pair)?.build(pairs, registry),
+        Rule::braced => parse_argument(pair, registry),
+        Rule::fraction => {
+            let mut args = pair.into_inner();
+            let lhs = parse_argument(args.next().unwrap(), registry)?;
+            let rhs = parse_argument(args.next().unwrap(), registry)?;
+            Ok(Expr::BinOp {
+                lhs: Box::new(lhs),
+                op: Op::Divide,
+                rhs: Box::new(rhs),
+                span,
+            })
+        }
+        Rule::root => {
+            let mut args = pair.into_inner().collect::<Vec<_>>();
+            let radicand = parse_argument(args.pop().unwrap(), registry)?;
+            Ok(match args.pop() {
+                Some(index) => Expr::Function {
+                    name: "root".to_string(),
+                    args: vec![
+                        radicand,
+                        parse_expr(index.into_inner().next().unwrap(), registry)?,
+                    ],
+                    span,
+                },
+                None => Expr::Function {
+                    name: "sqrt".to_string(),
+                    args: vec![radicand],
+                    span,
+                },
+            })
+        }
+        // The parentheses belong to the grouped expression
+        Rule::group => {
+            let text = pair.as_str();
+            let mut exprs = parse_group(pair, registry)?;
+            if exprs.len() > 1 {
+                let after = exprs[0].span().end - span.start;
+                let comma = span.start + after + text[after..].find(',').unwrap();
+                bail!(ParserError::UnexpectedToken(
+                    ",".to_string(),
+                    Span::new(comma, comma + 1)
+                ));
+            }
+            let mut expr = exprs.remove(0);
+            *expr.span_mut() = span;
+            Ok(expr)
+        }
+        rule => bail!(ParserError::InvalidToken(format!("{:?}", rule), span)),
+    }
+}
+
+fn parse_expr(expr: Pair<Rule>, registry: &OperatorRegistry) -> Result<Expr> {
+    let mut items = Vec::new();
+    let mut pairs = expr.into_inner().peekable();
+    while let Some(pair) = pairs.next() {
+        let span = Span::from(pair.as_span());
+        match pair.as_rule() {
+            Rule::operator => push_symbols(&mut items, pair.as_str(), span, registry)?,
+            Rule::command_operator => {
+                let symbol = match pair.as_str() {
+                    "\\div" => "/",
+                    "\\%" => "%",
+                    "\\le" | "\\leq" => "≤",
+                    "\\ge" | "\\geq" => "≥",
+                    "\\ne" | "\\neq" => "≠",
+                    "\\lt" => "<",
+                    "\\gt" => ">",
+                    _ => "*",
+                };
+                items.push(Item::Symbol(symbol.to_string(), span));
+            }
+            Rule::bar => items.push(Item::Bar(span)),
+            Rule::superscript => {
+                let caret = Span::new(span.start, span.start + 1);
+                items.push(Item::Symbol("^".to_string(), caret));
+                items.push(Item::Operand {
+                    expr: parse_argument(pair.into_inner().next().unwrap(), registry)?,
+                    literal: false,
+                });
+            }
+            Rule::number => items.push(Item::Operand {
+                expr: Expr::Number(parse_number(pair.as_str(), span)?, span),
+                literal: true,
+            }),
+            _ => items.push(Item::Operand {
+                expr: parse_operand(pair, &mut pairs, registry)?,
+                literal: false,
+            }),
+        }
+    }
+    build_expr(items, registry)
+}
+
+/// Parses the LaTeX subset emitted by math editors, such as `\frac{1}{2}\sqrt[3]{x}`,
+/// into the same tree as the equivalent plain expression.
+pub fn parse_latex(expression: &str) -> Result<Expr> {
+    parse_latex_with_config(expression, &parse_config())
+}
+
+/// Parses LaTeX with its own separators instead of the global ones, such as `3,5`
+/// for 3.5.
+pub fn parse_latex_with_config(expression: &str, config: &ParseConfig) -> Result<Expr> {
+    let expression = config.normalize(expression);
+    let mut pairs = match LatexParser::parse(Rule::latex, &expression) {
+        Ok(pairs) => pairs,
+        Err(err) => bail!(syntax_error(&expression, err)),
+    };
+    parse_expr(pairs.next().unwrap(), &operators())
+}
@@ -1,5 +1,31 @@
+mod config;
+mod latex;
+mod number;
+mod operators;
+#[allow(clippy::module_inception)]
 mod parser;
+mod resolver;
+mod span;
 mod token;
+mod tokenizer;
+mod tolerant;
 
-pub use parser::{parse, parse_equation};
-pub use token::{Expr, Op, Optimize};
\ No newline at end of file
+pub use crate::math::Rational;
+pub use config::{parse_config, set_parse_config, ParseConfig};
+pub use latex::{parse_latex, parse_latex_with_config};
+pub use operators::{
+    operators, precedence, register_operator, Assoc, Fixity, Operator, OperatorFn,
+    OperatorRegistry, Semantics,
+};
+pub use parser::{
+    parse, parse_equation, parse_statements, parse_statements_with_config, parse_with_config,
+    parse_with_operators,
+};
+pub use resolver::{resolve_name, Name};
+pub use span::Span;
+pub(crate) use token::merge_factors;
+pub use token::{
+    Expr, Logic, Op, Optimize, Relation, Series, SetOp, Statement, UnaryOp,
+};
+pub use tokenizer::{tokenize, tokenize_with_config, Token, TokenKind, TokenStream};
+pub use tolerant::{parse_tolerant, parse_tolerant_with_config, PartialParse};
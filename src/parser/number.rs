@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+
+use crate::error::ParserError;
+
+use super::Span;
+
+/// Converts the text matched by the `number` rule into its value.
+pub(crate) fn parse_number(text: &str, span: Span) -> Result<f64> {
+    let digits = text.replace('_', "");
+
+    let radix = match digits.get(..2) {
+        Some("0x") => Some(16),
+        Some("0b") => Some(2),
+        Some("0o") => Some(8),
+        _ => None,
+    };
+    if let Some(radix) = radix {
+        return match parse_radix(&digits[2..], radix) {
+            Some(val) => Ok(val),
+            None => bail!(ParserError::InvalidNumber(text.to_string(), span)),
+        };
+    }
+
+    match digits.find('(') {
+        Some(open) => parse_repeating(text, span, &digits, open),
+        None => match digits.parse::<f64>() {
+            Ok(val) => Ok(val),
+            Err(_) => bail!(ParserError::InvalidNumber(text.to_string(), span)),
+        },
+    }
+}
+
+/// Parses the digits of a hex, binary or octal literal. They are exact up to the range
+/// of `u128` and accumulated as a float beyond it, so any size that a decimal literal
+/// accepts works.
+fn parse_radix(digits: &str, radix: u32) -> Option<f64> {
+    if let Ok(value) = u128::from_str_radix(digits, radix) {
+        return Some(value as f64);
+    }
+    digits.chars().try_fold(0.0, |value: f64, digit| {
+        Some(value * radix as f64 + digit.to_digit(radix)? as f64)
+    })
+}
+
+/// Repeating decimals such as `0.1(6)` are split into the finite part `0.1` and the
+/// repetend `6`, which contributes `6 / (10^1 * (10^1 - 1))`.
+fn parse_repeating(text: &str, span: Span, digits: &str, open: usize) -> Result<f64> {
+    let invalid = || ParserError::InvalidNumber(text.to_string(), span);
+    let close = match digits.find(')') {
+        Some(close) => close,
+        None => bail!(invalid()),
+    };
+
+    let finite = &digits[..open];
+    let repetend = &digits[open + 1..close];
+    let scale = match digits[close + 1..].get(1..) {
+        Some(exponent) if !exponent.is_empty() => exponent.parse::<i32>().map_err(|_| invalid())?,
+        _ => 0,
+    };
+
+    let decimals = finite
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len()) as i32;
+    let finite = finite
+        .trim_end_matches('.')
+        .parse::<f64>()
+        .map_err(|_| invalid())?;
+    let repetend_value = repetend.parse::<f64>().map_err(|_| invalid())?;
+    let repeated =
+        repetend_value / (10f64.powi(decimals) * (10f64.powi(repetend.len() as i32) - 1.0));
+
+    Ok((finite + repeated) * 10f64.powi(scale))
+}
+
+/// Parses superscript digits such as `⁻¹²` into a number.
+pub(crate) fn parse_superscript(text: &str, span: Span) -> Result<f64> {
+    let digits: String = text
+        .chars()
+        .map(|c| match c {
+            '⁻' => '-',
+            '¹' => '1',
+            '²' => '2',
+            '³' => '3',
+            // ⁰ and ⁴ to ⁹ are consecutive code points
+            c => char::from_u32(c as u32 - '⁰' as u32 + '0' as u32).unwrap_or(c),
+        })
+        .collect();
+    match digits.parse() {
+        Ok(value) => Ok(value),
+        Err(_) => bail!(ParserError::InvalidNumber(text.to_string(), span)),
+    }
+}
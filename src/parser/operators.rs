@@ -0,0 +1,299 @@
+use std::fmt;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use super::{Logic, Op, Relation, SetOp, UnaryOp};
+
+/// Precedences of the built-in operators. Higher values bind tighter.
+pub mod precedence {
+    /// `X -> X^2 + 1` takes the whole expression on its right as the body.
+    pub const LAMBDA: u32 = 5;
+    pub const IMPLIES: u32 = 6;
+    pub const OR: u32 = 7;
+    pub const XOR: u32 = 8;
+    pub const AND: u32 = 9;
+    /// `not X < 3` negates the whole comparison.
+    pub const NOT: u32 = 10;
+    pub const EQUALS: u32 = 10;
+    /// `X in A union B` tests membership of the whole union.
+    pub const ELEMENT: u32 = 10;
+    pub const UNION: u32 = 12;
+    pub const INTERSECTION: u32 = 13;
+    /// `1..n+1` ranges over whole expressions.
+    pub const RANGE: u32 = 15;
+    pub const ADDITIVE: u32 = 20;
+    pub const MULTIPLICATIVE: u32 = 30;
+    /// Juxtaposition such as `2(3+4)` binds tighter than explicit `*` and `/`.
+    pub const IMPLICIT_MULTIPLY: u32 = 35;
+    pub const POWER: u32 = 40;
+    pub const PREFIX: u32 = 50;
+    pub const POSTFIX: u32 = 60;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fixity {
+    Infix(Assoc),
+    Prefix,
+    Postfix,
+}
+
+type NumericFn = dyn Fn(&[f64]) -> f64 + Send + Sync;
+
+/// Numeric implementation of a custom operator. Receives one value per operand.
+#[derive(Clone)]
+pub struct OperatorFn(pub Arc<NumericFn>);
+
+impl OperatorFn {
+    pub fn new(function: impl Fn(&[f64]) -> f64 + Send + Sync + 'static) -> OperatorFn {
+        OperatorFn(Arc::new(function))
+    }
+
+    pub fn call(&self, operands: &[f64]) -> f64 {
+        (self.0)(operands)
+    }
+}
+
+impl fmt::Debug for OperatorFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OperatorFn")
+    }
+}
+
+impl PartialEq for OperatorFn {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// What an operator turns into once it has been parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Semantics {
+    /// A built-in binary operation, `×` can be registered as `Binary(Op::Multiply)`.
+    Binary(Op),
+    /// A built-in unary operation such as the factorial.
+    Unary(UnaryOp),
+    Negate,
+    /// A logical connective between two booleans.
+    Logic(Logic),
+    Not,
+    /// An operation on sets, or the membership test `in`.
+    Set(SetOp),
+    /// A comparison. Neighbouring relations of the same precedence form a chain.
+    Relation(Relation),
+    /// A call to a function with the operands as its arguments, `√x` is `sqrt(x)`.
+    Function(String),
+    /// An anonymous function with the variable on the left as its parameter.
+    Lambda,
+    Custom(OperatorFn),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operator {
+    /// Either a run of symbol characters such as `+` or `<=`, or a keyword such as `mod`.
+    pub symbol: String,
+    pub fixity: Fixity,
+    pub precedence: u32,
+    pub semantics: Semantics,
+}
+
+impl Operator {
+    pub fn infix(symbol: &str, precedence: u32, assoc: Assoc, semantics: Semantics) -> Operator {
+        Operator {
+            symbol: symbol.to_string(),
+            fixity: Fixity::Infix(assoc),
+            precedence,
+            semantics,
+        }
+    }
+
+    pub fn prefix(symbol: &str, precedence: u32, semantics: Semantics) -> Operator {
+        Operator {
+            symbol: symbol.to_string(),
+            fixity: Fixity::Prefix,
+            precedence,
+            semantics,
+        }
+    }
+
+    pub fn postfix(symbol: &str, precedence: u32, semantics: Semantics) -> Operator {
+        Operator {
+            symbol: symbol.to_string(),
+            fixity: Fixity::Postfix,
+            precedence,
+            semantics,
+        }
+    }
+
+    /// Keyword operators are written like identifiers and must be surrounded by spaces.
+    pub fn is_keyword(&self) -> bool {
+        self.symbol.starts_with(|c: char| c.is_ascii_alphabetic())
+    }
+}
+
+/// The set of operators known to the parser. A symbol can be registered once per
+/// position, `%` for example is both the postfix percent and the infix modulo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorRegistry {
+    operators: Vec<Operator>,
+}
+
+impl OperatorRegistry {
+    /// A registry without any operators.
+    pub fn empty() -> OperatorRegistry {
+        OperatorRegistry {
+            operators: Vec::new(),
+        }
+    }
+
+    /// Adds an operator, replacing the one with the same symbol in the same position.
+    pub fn add(&mut self, operator: Operator) {
+        let position = |fixity: Fixity| match fixity {
+            Fixity::Infix(_) => 0,
+            Fixity::Prefix => 1,
+            Fixity::Postfix => 2,
+        };
+        self.operators.retain(|existing| {
+            existing.symbol != operator.symbol
+                || position(existing.fixity) != position(operator.fixity)
+        });
+        self.operators.push(operator);
+    }
+
+    pub fn infix(&self, symbol: &str) -> Option<&Operator> {
+        self.find(symbol, |fixity| matches!(fixity, Fixity::Infix(_)))
+    }
+
+    pub fn prefix(&self, symbol: &str) -> Option<&Operator> {
+        self.find(symbol, |fixity| fixity == Fixity::Prefix)
+    }
+
+    pub fn postfix(&self, symbol: &str) -> Option<&Operator> {
+        self.find(symbol, |fixity| fixity == Fixity::Postfix)
+    }
+
+    pub fn is_keyword(&self, word: &str) -> bool {
+        self.operators
+            .iter()
+            .any(|operator| operator.is_keyword() && operator.symbol == word)
+    }
+
+    /// Splits a run of symbol characters such as `*-` into registered symbols, always
+    /// taking the longest symbol that matches. Returns the offending rest on failure.
+    pub fn split_symbols<'a>(&self, run: &'a str) -> Result<Vec<&'a str>, &'a str> {
+        let mut symbols = Vec::new();
+        let mut rest = run;
+        while !rest.is_empty() {
+            let longest = self
+                .operators
+                .iter()
+                .filter(|operator| !operator.is_keyword() && rest.starts_with(&operator.symbol))
+                .map(|operator| operator.symbol.len())
+                .max();
+            match longest {
+                Some(len) => {
+                    symbols.push(&rest[..len]);
+                    rest = &rest[len..];
+                }
+                None => return Err(rest),
+            }
+        }
+        Ok(symbols)
+    }
+
+    fn find(&self, symbol: &str, fixity: impl Fn(Fixity) -> bool) -> Option<&Operator> {
+        self.operators
+            .iter()
+            .find(|operator| operator.symbol == symbol && fixity(operator.fixity))
+    }
+}
+
+impl Default for OperatorRegistry {
+    fn default() -> Self {
+        use precedence::*;
+        use Assoc::*;
+
+        let mut registry = OperatorRegistry::empty();
+        for operator in [
+            Operator::infix("->", LAMBDA, Right, Semantics::Lambda),
+            Operator::infix("implies", IMPLIES, Right, Semantics::Logic(Logic::Implies)),
+            Operator::infix("or", OR, Left, Semantics::Logic(Logic::Or)),
+            Operator::infix("xor", XOR, Left, Semantics::Logic(Logic::Xor)),
+            Operator::infix("and", AND, Left, Semantics::Logic(Logic::And)),
+            Operator::prefix("not", NOT, Semantics::Not),
+            Operator::infix("in", ELEMENT, Left, Semantics::Set(SetOp::Element)),
+            Operator::infix("union", UNION, Left, Semantics::Set(SetOp::Union)),
+            Operator::infix("\\", UNION, Left, Semantics::Set(SetOp::Difference)),
+            Operator::infix(
+                "intersect",
+                INTERSECTION,
+                Left,
+                Semantics::Set(SetOp::Intersection),
+            ),
+            Operator::infix("=", EQUALS, Left, Semantics::Relation(Relation::Equal)),
+            Operator::infix("!=", EQUALS, Left, Semantics::Relation(Relation::NotEqual)),
+            Operator::infix("<", EQUALS, Left, Semantics::Relation(Relation::Less)),
+            Operator::infix("<=", EQUALS, Left, Semantics::Relation(Relation::LessEqual)),
+            Operator::infix(">", EQUALS, Left, Semantics::Relation(Relation::Greater)),
+            Operator::infix(
+                ">=",
+                EQUALS,
+                Left,
+                Semantics::Relation(Relation::GreaterEqual),
+            ),
+            Operator::infix("..", RANGE, Left, Semantics::Function("range".to_string())),
+            Operator::infix("+", ADDITIVE, Left, Semantics::Binary(Op::Add)),
+            Operator::infix("-", ADDITIVE, Left, Semantics::Binary(Op::Subtract)),
+            Operator::infix("*", MULTIPLICATIVE, Left, Semantics::Binary(Op::Multiply)),
+            Operator::infix("/", MULTIPLICATIVE, Left, Semantics::Binary(Op::Divide)),
+            Operator::infix("%", MULTIPLICATIVE, Left, Semantics::Binary(Op::Modulo)),
+            Operator::infix("^", POWER, Right, Semantics::Binary(Op::Power)),
+            Operator::prefix("-", PREFIX, Semantics::Negate),
+            Operator::postfix("!", POSTFIX, Semantics::Unary(UnaryOp::Factorial)),
+            Operator::postfix("!!", POSTFIX, Semantics::Unary(UnaryOp::DoubleFactorial)),
+            Operator::postfix("%", POSTFIX, Semantics::Unary(UnaryOp::Percent)),
+            // Unicode symbols found in text pasted from documents and phone keyboards
+            Operator::infix("≤", EQUALS, Left, Semantics::Relation(Relation::LessEqual)),
+            Operator::infix(
+                "≥",
+                EQUALS,
+                Left,
+                Semantics::Relation(Relation::GreaterEqual),
+            ),
+            Operator::infix("→", LAMBDA, Right, Semantics::Lambda),
+            Operator::infix("∈", ELEMENT, Left, Semantics::Set(SetOp::Element)),
+            Operator::infix("∪", UNION, Left, Semantics::Set(SetOp::Union)),
+            Operator::infix("∩", INTERSECTION, Left, Semantics::Set(SetOp::Intersection)),
+            Operator::infix("≠", EQUALS, Left, Semantics::Relation(Relation::NotEqual)),
+            Operator::infix("−", ADDITIVE, Left, Semantics::Binary(Op::Subtract)),
+            Operator::infix("×", MULTIPLICATIVE, Left, Semantics::Binary(Op::Multiply)),
+            Operator::infix("·", MULTIPLICATIVE, Left, Semantics::Binary(Op::Multiply)),
+            Operator::infix("⋅", MULTIPLICATIVE, Left, Semantics::Binary(Op::Multiply)),
+            Operator::infix("÷", MULTIPLICATIVE, Left, Semantics::Binary(Op::Divide)),
+            Operator::prefix("−", PREFIX, Semantics::Negate),
+            Operator::prefix("√", PREFIX, Semantics::Function("sqrt".to_string())),
+            Operator::prefix("∛", PREFIX, Semantics::Function("cbrt".to_string())),
+        ] {
+            registry.add(operator);
+        }
+        registry
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref OPERATORS: RwLock<OperatorRegistry> = RwLock::new(OperatorRegistry::default());
+}
+
+/// Adds an operator to the registry used by [`parse`](super::parse).
+pub fn register_operator(operator: Operator) {
+    OPERATORS.write().unwrap().add(operator);
+}
+
+/// The registry used by [`parse`](super::parse).
+pub fn operators() -> RwLockReadGuard<'static, OperatorRegistry> {
+    OPERATORS.read().unwrap()
+}
@@ -1,148 +1,1390 @@
 use anyhow::{bail, Result};
-use pest::pratt_parser::PrattParser;
+use pest::error::InputLocation;
+use pest::iterators::Pair;
 use pest::Parser;
-use pest::{iterators::Pairs, pratt_parser::Assoc};
 
 use crate::error::ParserError;
-use crate::math::CONSTANTS_DATABASE;
 
-use super::{Expr, Op};
+use super::config::parse_config;
+use super::number::{parse_number, parse_superscript};
+use super::operators::{operators, precedence};
+use super::{
+    merge_factors, resolve_name, Assoc, Expr, Fixity, Name, Op, Operator, OperatorRegistry,
+    ParseConfig, Rational, Relation, Semantics, Series, SetOp, Span, Statement,
+};
 
 #[derive(pest_derive::Parser)]
 #[grammar = "grammar/sedenion.pest"]
 pub(crate) struct CalculatorParser;
 
-lazy_static::lazy_static! {
-    static ref PRATT_PARSER: PrattParser<Rule> = {
-        use pest::pratt_parser::{Assoc::*, Op};
-        use Rule::*;
-
-        // Precedence is defined lowest to highest
-        PrattParser::new()
-            .op(Op::infix(add, Left) | Op::infix(subtract, Left))
-            .op(Op::infix(multiply, Left) | Op::infix(divide, Left) | Op::infix(modulo, Left))
-            .op(Op::infix(power, Right))
-            .op(Op::prefix(unary_minus))
-            .op(Op::infix(equals, Left))
-        };
+/// A term of an expression before operator precedence has been applied.
+pub(super) enum Item {
+    Operand { expr: Expr, literal: bool },
+    Symbol(String, Span),
+    Bar(Span),
+    /// `(a, b)`, either the parameters of a lambda or an open interval.
+    Tuple(Vec<Expr>, Span),
+    /// `d/dX` or `d^2/dX^2`, which differentiates the operand after it.
+    Derivative {
+        variable: String,
+        order: u32,
+        span: Span,
+    },
+}
+
+impl Item {
+    fn span(&self) -> Span {
+        match self {
+            Item::Operand { expr, .. } => expr.span(),
+            Item::Symbol(_, span) | Item::Bar(span) | Item::Tuple(_, span) => *span,
+            Item::Derivative { span, .. } => *span,
+        }
+    }
 }
 
-fn parse_function(pairs: Pairs<Rule>) -> Result<Expr> {
+fn parse_function(function: Pair<Rule>, registry: &OperatorRegistry) -> Result<Expr> {
+    let span = Span::from(function.as_span());
     let mut name = String::new();
-    let mut args: Vec<Box<Expr>> = Vec::new();
+    let mut name_span = span;
+    let mut primes = 0;
+    let mut args = Vec::new();
 
-    for pair in pairs {
+    for pair in function.into_inner() {
         match pair.as_rule() {
-            Rule::function_name => name = String::from(pair.as_str()),
+            Rule::identifier => {
+                name = String::from(pair.as_str());
+                name_span = pair.as_span().into();
+            }
+            Rule::primes => primes = pair.as_str().len() as u32,
+            Rule::function_args if name == "piecewise" => {
+                let cases = pair
+                    .into_inner()
+                    .map(|arg| parse_case(arg, registry))
+                    .collect::<Result<Vec<Case>>>()?;
+                let call = build_piecewise(cases, span)?;
+                return differentiate_call(call, (&name, name_span), primes);
+            }
             Rule::function_args => {
                 args = pair
                     .into_inner()
-                    .map(|arg| parse_expr(arg.into_inner()))
-                    .map(|arg| Box::new(arg.unwrap()))
-                    .collect()
+                    .map(|arg| parse_expr(arg, registry))
+                    .collect::<Result<Vec<Expr>>>()?
             }
             rule => {
-                bail!(ParserError::InvalidToken(format!("{:?}", rule)))
+                bail!(ParserError::InvalidToken(
+                    format!("{:?}", rule),
+                    pair.as_span().into()
+                ))
             }
         }
     }
 
-    if name != "" {
-        Ok(Expr::Function { name, args })
-    } else {
-        bail!(ParserError::NoFunctionName)
+    if name.is_empty() {
+        bail!(ParserError::NoFunctionName(span))
     }
+    let call = build_function((name.clone(), name_span), args, span)?;
+    differentiate_call(call, (&name, name_span), primes)
 }
 
-fn parse_monomial(pairs: Pairs<Rule>) -> Result<Expr> {
-    let mut coefficient: Option<f64> = None;
-    let mut exponent: Option<f64> = None;
-    let mut variable: Option<String> = None;
-    for pair in pairs {
+/// Wraps a call written with primes such as `f''(X)` in its derivative. Only
+/// user-defined functions, whose names resolve to variables, can take primes.
+pub(super) fn differentiate_call(
+    call: Expr,
+    (name, name_span): (&str, Span),
+    primes: u32,
+) -> Result<Expr> {
+    if primes > 0 && !matches!(resolve_name(name), Name::Variable) {
+        bail!(ParserError::BuiltinPrime(name.to_string(), name_span));
+    }
+    match call {
+        call if primes == 0 => Ok(call),
+        Expr::Function { ref args, span, .. } if args.len() == 1 => Ok(Expr::Derivative {
+            expr: Box::new(call),
+            variable: None,
+            order: primes,
+            span,
+        }),
+        call => bail!(ParserError::InvalidPrime(call.to_string(), call.span())),
+    }
+}
+
+pub(super) fn build_function(
+    (name, name_span): (String, Span),
+    mut args: Vec<Expr>,
+    span: Span,
+) -> Result<Expr> {
+    // Only user-defined functions can be called without arguments, as in `f()`
+    if args.is_empty() && !matches!(resolve_name(&name), Name::Variable) {
+        bail!(ParserError::MissingArguments(name, name_span));
+    }
+
+    // `if(condition, a, b)` is the piecewise expression with a single case
+    if let ("if", 3) = (name.as_str(), args.len()) {
+        let mut args = args.into_iter();
+        let condition = args.next().unwrap();
+        let value = args.next().unwrap();
+        return Ok(Expr::Piecewise {
+            cases: vec![(value, condition)],
+            otherwise: args.next().map(Box::new),
+            span,
+        });
+    }
+
+    // `sum(k, 1, n, body)` binds `k` in the body, while `sum(list)` stays a function
+    if let ("sum" | "prod", 4) = (name.as_str(), args.len()) {
+        let mut args = args.into_iter();
+        let index = args.next().unwrap();
+        let variable = match index.variable() {
+            Some(variable) => variable.to_string(),
+            None => bail!(ParserError::InvalidIndexVariable(
+                index.to_string(),
+                index.span()
+            )),
+        };
+        return Ok(Expr::Series {
+            kind: match name.as_str() {
+                "sum" => Series::Sum,
+                _ => Series::Product,
+            },
+            variable,
+            lower: Box::new(args.next().unwrap()),
+            upper: Box::new(args.next().unwrap()),
+            body: Box::new(args.next().unwrap()),
+            span,
+        });
+    }
+
+    // `diff(expr, X)` and `diff(expr, X, 2)` stay unevaluated
+    if let ("diff", 2 | 3) = (name.as_str(), args.len()) {
+        let mut args = args.into_iter();
+        let expr = args.next().unwrap();
+        let variable = args.next().unwrap();
+        let variable = match variable.variable() {
+            Some(name) => name.to_string(),
+            None => bail!(ParserError::InvalidDerivativeVariable(
+                variable.to_string(),
+                variable.span()
+            )),
+        };
+        let order = match args.next() {
+            Some(Expr::Number(order, order_span)) => {
+                derivative_order(order, &order.to_string(), order_span)?
+            }
+            Some(order) => bail!(ParserError::InvalidDerivativeOrder(
+                order.to_string(),
+                order.span()
+            )),
+            None => 1,
+        };
+        return Ok(Expr::Derivative {
+            expr: Box::new(expr),
+            variable: Some(variable),
+            order,
+            span,
+        });
+    }
+
+    // A constant followed by parentheses such as `pi(2)` is a product, not a call
+    if let (Name::Constant(value), 1) = (resolve_name(&name), args.len()) {
+        return Ok(Expr::BinOp {
+            lhs: Box::new(Expr::Constant {
+                name,
+                value,
+                span: name_span,
+            }),
+            op: Op::Multiply,
+            rhs: Box::new(args.remove(0)),
+            span,
+        });
+    }
+
+    Ok(Expr::Function { name, args, span })
+}
+
+/// An argument of `piecewise`, either a `(value, condition)` case or the fallback.
+pub(super) enum Case {
+    Tuple(Vec<Expr>, Span),
+    Value(Expr),
+}
+
+fn parse_case(arg: Pair<Rule>, registry: &OperatorRegistry) -> Result<Case> {
+    let mut terms = arg.clone().into_inner();
+    match (terms.next(), terms.next()) {
+        (Some(tuple), None) if tuple.as_rule() == Rule::tuple => {
+            let span = Span::from(tuple.as_span());
+            let items = tuple
+                .into_inner()
+                .map(|expr| parse_expr(expr, registry))
+                .collect::<Result<Vec<Expr>>>()?;
+            Ok(Case::Tuple(items, span))
+        }
+        _ => Ok(Case::Value(parse_expr(arg, registry)?)),
+    }
+}
+
+/// Builds a piecewise expression out of its cases, of which only the last one may
+/// be a plain fallback value.
+pub(super) fn build_piecewise(args: Vec<Case>, span: Span) -> Result<Expr> {
+    let mut cases = Vec::new();
+    let mut otherwise = None;
+    let count = args.len();
+    for (i, arg) in args.into_iter().enumerate() {
+        match arg {
+            Case::Tuple(items, tuple_span) => match <[Expr; 2]>::try_from(items) {
+                Ok([value, condition]) => cases.push((value, condition)),
+                Err(_) => bail!(ParserError::InvalidPiecewise(tuple_span)),
+            },
+            Case::Value(expr) if i + 1 == count && !cases.is_empty() => {
+                otherwise = Some(Box::new(expr))
+            }
+            Case::Value(expr) => bail!(ParserError::InvalidPiecewise(expr.span())),
+        }
+    }
+    Ok(Expr::Piecewise {
+        cases,
+        otherwise,
+        span,
+    })
+}
+
+fn parse_monomial(monomial: Pair<Rule>) -> Result<Expr> {
+    let mut coefficient: Option<(f64, Span)> = None;
+    // Every name with the exponent written right after it
+    let mut factors = Vec::new();
+    for pair in monomial.into_inner() {
+        let pair_span = Span::from(pair.as_span());
         match pair.as_rule() {
-            Rule::coefficient => coefficient = Some(pair.as_str().parse::<f64>()?),
-            Rule::variable => variable = Some(pair.as_str().to_string()),
+            Rule::coefficient => {
+                coefficient = Some((parse_number(pair.as_str(), pair_span)?, pair_span))
+            }
+            Rule::identifier => factors.extend(
+                split_variables(pair.as_str(), pair_span)
+                    .into_iter()
+                    .map(|name| (name, None)),
+            ),
             Rule::exponent => {
-                let pair = match pair.as_str().strip_prefix("^") {
-                    Some(val) => val,
-                    None => bail!(ParserError::InvalidToken(format!("{:?}", pair.as_str()))),
+                let exponent = parse_exponent(pair)?;
+                factors.last_mut().unwrap().1 = Some((exponent, pair_span));
+            }
+            Rule::superscript => {
+                let exponent = parse_superscript(pair.as_str(), pair_span)?;
+                factors.last_mut().unwrap().1 = Some((exponent, pair_span));
+            }
+            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule), pair_span)),
+        }
+    }
+
+    // The coefficient belongs to the first factor, the others multiply it
+    let mut factors = factors.into_iter();
+    let (name, exponent) = factors.next().unwrap();
+    let end = exponent.map_or(name.1, |(_, span)| span);
+    let first_span = coefficient.map_or(name.1, |(_, span)| span).merge(end);
+    let mut expr = build_monomial(coefficient, name, exponent, first_span)?;
+    for (name, exponent) in factors {
+        let span = exponent.map_or(name.1, |(_, span)| name.1.merge(span));
+        expr = multiply_factors(expr, build_monomial(None, name, exponent, span)?);
+    }
+    Ok(expr)
+}
+
+/// Splits a run of capital letters such as `XY` into single-letter variables. Other
+/// names, including lowercase ones such as `nand`, are kept whole.
+fn split_variables(name: &str, span: Span) -> Vec<(String, Span)> {
+    if !is_product(name) {
+        return vec![(name.to_string(), span)];
+    }
+    name.char_indices()
+        .map(|(offset, letter)| {
+            let start = span.start + offset;
+            (letter.to_string(), Span::new(start, start + 1))
+        })
+        .collect()
+}
+
+/// Whether a name is read as the product of its letters.
+fn is_product(name: &str) -> bool {
+    name.len() > 1 && name.bytes().all(|c| c.is_ascii_uppercase())
+}
+
+/// Parses `^2`, `^-1` or `^(1/2)` into the value of the exponent.
+fn parse_exponent(exponent: Pair<Rule>) -> Result<f64> {
+    let span = Span::from(exponent.as_span());
+    let text = exponent.as_str();
+    let mut parts = exponent.into_inner().map(|signed| -> Result<f64> {
+        let signed_span = Span::from(signed.as_span());
+        let digits = signed.as_str().trim_start_matches('-');
+        let value = parse_number(digits, signed_span)?;
+        match signed.as_str().starts_with('-') {
+            true => Ok(-value),
+            false => Ok(value),
+        }
+    });
+    let numerator = parts.next().unwrap()?;
+    match parts.next() {
+        None => Ok(numerator),
+        Some(denominator) => {
+            let denominator = denominator?;
+            if denominator == 0.0 {
+                bail!(ParserError::InvalidExponent(text.to_string(), span));
+            }
+            Ok(numerator / denominator)
+        }
+    }
+}
+
+/// Multiplies the factors of a monomial such as `2X^2Y`. Variables are gathered in a
+/// single monomial, while constants such as the `e` of `X^2e` stay explicit products.
+fn multiply_factors(lhs: Expr, rhs: Expr) -> Expr {
+    let span = lhs.span().merge(rhs.span());
+    if let (
+        Expr::Monomial {
+            coefficient,
+            factors,
+            ..
+        },
+        Expr::Monomial {
+            coefficient: other_coefficient,
+            factors: other_factors,
+            ..
+        },
+    ) = (&lhs, &rhs)
+    {
+        if let Some(factors) = merge_factors([factors.clone(), other_factors.clone()].concat()) {
+            return Expr::Monomial {
+                coefficient: coefficient * other_coefficient,
+                factors,
+                span,
+            };
+        }
+    }
+    Expr::BinOp {
+        lhs: Box::new(lhs),
+        op: Op::Multiply,
+        rhs: Box::new(rhs),
+        span,
+    }
+}
+
+/// Builds a monomial, or an explicit product and power when the name is a constant.
+pub(super) fn build_monomial(
+    coefficient: Option<(f64, Span)>,
+    (name, name_span): (String, Span),
+    exponent: Option<(f64, Span)>,
+    span: Span,
+) -> Result<Expr> {
+    match resolve_name(&name) {
+        Name::Variable => {
+            let fraction = match exponent {
+                Some((value, _)) => Rational::approximate(value),
+                None => Some(Rational::ONE),
+            };
+            match fraction {
+                Some(fraction) => Ok(Expr::Monomial {
+                    coefficient: coefficient.map_or(1.0, |(value, _)| value),
+                    factors: vec![(name, fraction)],
+                    span,
+                }),
+                // Exponents that aren't small fractions, such as `X^1e20`, stay an
+                // explicit power of the variable
+                None => {
+                    let variable = Expr::Monomial {
+                        coefficient: 1.0,
+                        factors: vec![(name, Rational::ONE)],
+                        span: name_span,
+                    };
+                    Ok(raise(variable, coefficient, exponent, span))
+                }
+            }
+        }
+        // Constants are not variables, so `2pi^2` is kept as an explicit product
+        Name::Constant(value) => {
+            let constant = Expr::Constant {
+                name,
+                value,
+                span: name_span,
+            };
+            Ok(raise(constant, coefficient, exponent, span))
+        }
+        Name::Bool(value) => match (coefficient, exponent) {
+            (None, None) => Ok(Expr::Bool(value, span)),
+            _ => bail!(ParserError::UnexpectedToken(name, name_span)),
+        },
+        Name::Function => bail!(ParserError::MissingArguments(name, name_span)),
+    }
+}
+
+/// The explicit product `coefficient * base^exponent`.
+fn raise(
+    base: Expr,
+    coefficient: Option<(f64, Span)>,
+    exponent: Option<(f64, Span)>,
+    span: Span,
+) -> Expr {
+    let mut expr = base;
+    if let Some((exponent, exponent_span)) = exponent {
+        expr = Expr::BinOp {
+            span: expr.span().merge(exponent_span),
+            lhs: Box::new(expr),
+            op: Op::Power,
+            rhs: Box::new(Expr::Number(exponent, exponent_span)),
+        };
+    }
+    if let Some((coefficient, coefficient_span)) = coefficient {
+        expr = Expr::BinOp {
+            lhs: Box::new(Expr::Number(coefficient, coefficient_span)),
+            op: Op::Multiply,
+            rhs: Box::new(expr),
+            span,
+        };
+    }
+    expr
+}
+
+/// Name of a `function` or `loose_function` pair.
+fn function_name<'i>(function: &Pair<'i, Rule>) -> &'i str {
+    function.clone().into_inner().next().unwrap().as_str()
+}
+
+/// Whether a call is a keyword operator before parentheses, as in `not (a or b)`.
+/// With primes, as in `not'(a)`, it stays a call.
+pub(super) fn is_keyword_call(function: &Pair<Rule>, registry: &OperatorRegistry) -> bool {
+    let mut inner = function.clone().into_inner();
+    let name = inner.next().unwrap().as_str();
+    registry.is_keyword(name) && inner.next().map(|pair| pair.as_rule()) != Some(Rule::primes)
+}
+
+/// Splits a keyword operator written like a call, as in `not (a or b)`, into the
+/// keyword symbol and the span of the parentheses after it.
+fn split_keyword(function: &Pair<Rule>) -> (Item, Span) {
+    let span = function.as_span();
+    let keyword = function_name(function);
+    let open = span.start() + function.as_str().find('(').unwrap();
+    let symbol = Span::new(span.start(), span.start() + keyword.len());
+    (
+        Item::Symbol(keyword.to_string(), symbol),
+        Span::new(open, span.end()),
+    )
+}
+
+/// Parses `d/dX`, `d^2/dX^2` or `d²/dX²`, whose orders have to agree.
+fn parse_derivative(derivative: Pair<Rule>) -> Result<Item> {
+    let span = Span::from(derivative.as_span());
+    let text = derivative.as_str();
+    let mut variable = None;
+    let mut orders = (None, None);
+    for pair in derivative.into_inner() {
+        let pair_span = Span::from(pair.as_span());
+        match pair.as_rule() {
+            Rule::identifier => {
+                let name = pair.as_str();
+                if !matches!(resolve_name(name), Name::Variable) || is_product(name) {
+                    bail!(ParserError::InvalidDerivativeVariable(
+                        name.to_string(),
+                        pair_span
+                    ));
+                }
+                variable = Some(name.to_string());
+            }
+            Rule::derivative_order => {
+                let order = match pair.as_str().strip_prefix('^') {
+                    Some(digits) => parse_number(digits, pair_span)?,
+                    None => parse_superscript(pair.as_str(), pair_span)?,
                 };
-                exponent = Some(pair.parse::<f64>()?);
+                let order = derivative_order(order, text, span)?;
+                match variable {
+                    None => orders.0 = Some(order),
+                    Some(_) => orders.1 = Some(order),
+                }
             }
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
+            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule), pair_span)),
         }
     }
 
-    Ok(Expr::Monomial {
-        coefficient: coefficient.unwrap_or(1.0),
+    let order = match orders {
+        (None, None) => 1,
+        (Some(numerator), Some(denominator)) if numerator == denominator => numerator,
+        _ => bail!(ParserError::InvalidDerivativeOrder(text.to_string(), span)),
+    };
+    Ok(Item::Derivative {
         variable: variable.unwrap(),
-        exponent: exponent.unwrap_or(1.0),
+        order,
+        span,
     })
 }
 
-fn parse_expr(pairs: Pairs<Rule>) -> Result<Expr> {
-    PRATT_PARSER
-        .map_primary(|primary| match primary.as_rule() {
-            Rule::number => Ok(Expr::Number(primary.as_str().parse::<f64>().unwrap())),
-            Rule::expr => parse_expr(primary.into_inner()),
-            Rule::function => parse_function(primary.into_inner()),
-            Rule::monomial => parse_monomial(primary.into_inner()),
-            Rule::constant => {
-                let name = primary.as_str().to_string();
-                let value = *match CONSTANTS_DATABASE.get(&name) {
-                    Some(val) => val,
-                    None => bail!(ParserError::UnknownConstant(name.to_string())),
+/// The order of a derivative, which has to be a whole number from one up.
+fn derivative_order(order: f64, text: &str, span: Span) -> Result<u32> {
+    if order < 1.0 || order.fract() != 0.0 || order > u32::MAX as f64 {
+        bail!(ParserError::InvalidDerivativeOrder(text.to_string(), span));
+    }
+    Ok(order as u32)
+}
+
+/// How [`push_items`] parses bracketed terms and what happens when a step fails,
+/// which is all that differs between the strict and the tolerant parser.
+pub(super) trait TermParser {
+    fn registry(&self) -> &OperatorRegistry;
+
+    /// Passes on the result of a step. The strict parser stops at an error, while the
+    /// tolerant one records it and goes on with `fallback`.
+    fn recover<T>(&mut self, result: Result<T>, span: Span, fallback: T) -> Result<T>;
+
+    /// Parses a `function` or `loose_function` call.
+    fn function(&mut self, function: Pair<Rule>) -> Result<Expr>;
+
+    /// Parses the expressions in parentheses, of a group, a tuple or the call-like
+    /// keyword operator in `not (a or b)`.
+    fn parenthesized(&mut self, group: Pair<Rule>) -> Result<Vec<Expr>>;
+
+    /// Parses the expressions between the brackets of a list, set or interval.
+    fn bracketed(&mut self, pair: Pair<Rule>) -> Result<Vec<Expr>>;
+}
+
+/// Parses the `expr` rule, failing at the first error.
+struct StrictParser<'a> {
+    registry: &'a OperatorRegistry,
+}
+
+impl TermParser for StrictParser<'_> {
+    fn registry(&self) -> &OperatorRegistry {
+        self.registry
+    }
+
+    fn recover<T>(&mut self, result: Result<T>, _: Span, _: T) -> Result<T> {
+        result
+    }
+
+    fn function(&mut self, function: Pair<Rule>) -> Result<Expr> {
+        parse_function(function, self.registry)
+    }
+
+    fn parenthesized(&mut self, group: Pair<Rule>) -> Result<Vec<Expr>> {
+        let exprs = match group.as_rule() {
+            Rule::function => group.into_inner().nth(1).unwrap().into_inner(),
+            _ => group.into_inner(),
+        };
+        exprs.map(|expr| parse_expr(expr, self.registry)).collect()
+    }
+
+    fn bracketed(&mut self, pair: Pair<Rule>) -> Result<Vec<Expr>> {
+        pair.into_inner()
+            .map(|expr| parse_expr(expr, self.registry))
+            .collect()
+    }
+}
+
+/// Flattens the terms of an `expr` or `loose_expr` pair into operands, operator
+/// symbols and bars.
+pub(super) fn push_items(
+    parser: &mut impl TermParser,
+    items: &mut Vec<Item>,
+    expr: Pair<Rule>,
+) -> Result<()> {
+    for pair in expr.into_inner() {
+        let span = Span::from(pair.as_span());
+        match pair.as_rule() {
+            Rule::number => {
+                let number =
+                    parse_number(pair.as_str(), span).map(|value| Expr::Number(value, span));
+                items.push(Item::Operand {
+                    expr: parser.recover(number, span, Expr::Error(span))?,
+                    literal: true,
+                });
+            }
+            Rule::derivative => {
+                let fallback = Item::Operand {
+                    expr: Expr::Error(span),
+                    literal: false,
+                };
+                items.push(parser.recover(parse_derivative(pair), span, fallback)?);
+            }
+            // A keyword operator before parentheses, as in `not (a or b)`, isn't a call
+            Rule::function | Rule::loose_function if is_keyword_call(&pair, parser.registry()) => {
+                let (keyword, group) = split_keyword(&pair);
+                items.push(keyword);
+                push_group(items, parser.parenthesized(pair)?, group);
+            }
+            Rule::function | Rule::loose_function => items.push(Item::Operand {
+                expr: parser.function(pair)?,
+                literal: false,
+            }),
+            Rule::group | Rule::tuple | Rule::loose_group => {
+                push_group(items, parser.parenthesized(pair)?, span)
+            }
+            Rule::monomial if parser.registry().is_keyword(pair.as_str()) => {
+                items.push(Item::Symbol(pair.as_str().to_string(), span))
+            }
+            Rule::monomial => items.push(Item::Operand {
+                expr: parser.recover(parse_monomial(pair), span, Expr::Error(span))?,
+                literal: false,
+            }),
+            Rule::list | Rule::loose_list => {
+                let list = parser.bracketed(pair)?;
+                push_list(items, list, span);
+            }
+            Rule::interval | Rule::loose_interval => {
+                let closed = interval_ends(pair.as_str());
+                let mut ends = parser.bracketed(pair)?.into_iter();
+                let lower = ends.next().unwrap();
+                items.push(Item::Operand {
+                    expr: interval(lower, ends.next().unwrap(), closed, span),
+                    literal: false,
+                });
+            }
+            Rule::set | Rule::loose_set => items.push(Item::Operand {
+                expr: Expr::Set {
+                    items: parser.bracketed(pair)?,
+                    span,
+                },
+                literal: false,
+            }),
+            Rule::bar => items.push(Item::Bar(span)),
+            // `(1+2)²` is the same as `(1+2)^2`
+            Rule::superscript => {
+                let exponent =
+                    parse_superscript(pair.as_str(), span).map(|value| Expr::Number(value, span));
+                items.push(Item::Symbol("^".to_string(), span));
+                items.push(Item::Operand {
+                    expr: parser.recover(exponent, span, Expr::Error(span))?,
+                    literal: false,
+                });
+            }
+            // An unknown operator is dropped along with the rest of its run
+            Rule::operator => {
+                let result = push_symbols(items, pair.as_str(), span, parser.registry());
+                parser.recover(result, span, ())?;
+            }
+            rule => {
+                let err = ParserError::InvalidToken(format!("{:?}", rule), span);
+                parser.recover(Err(err.into()), span, ())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pushes the expressions of a parenthesized group, which are a tuple when there are
+/// several of them.
+fn push_group(items: &mut Vec<Item>, mut exprs: Vec<Expr>, span: Span) {
+    if exprs.len() > 1 {
+        items.push(Item::Tuple(exprs, span));
+    } else {
+        // The parentheses belong to the grouped expression
+        let mut expr = exprs.remove(0);
+        *expr.span_mut() = span;
+        items.push(Item::Operand {
+            expr,
+            literal: false,
+        });
+    }
+}
+
+/// Pushes a list literal, or indexes the operand right before it as in `v[2]`. Numbers
+/// can't be indexed, so `2[1, 2]` is a product.
+fn push_list(items: &mut Vec<Item>, list: Vec<Expr>, span: Span) {
+    if let Some(Item::Operand {
+        expr,
+        literal: false,
+    }) = items.last()
+    {
+        if expr.span().end == span.start {
+            let target = expr.clone();
+            items.pop();
+            items.push(Item::Operand {
+                expr: Expr::Index {
+                    span: target.span().merge(span),
+                    target: Box::new(target),
+                    indices: list,
+                },
+                literal: false,
+            });
+            return;
+        }
+    }
+    items.push(Item::Operand {
+        expr: Expr::List { items: list, span },
+        literal: false,
+    });
+}
+
+/// Splits a run of operator characters into registered symbols.
+pub(super) fn push_symbols(
+    items: &mut Vec<Item>,
+    run: &str,
+    span: Span,
+    registry: &OperatorRegistry,
+) -> Result<()> {
+    let symbols = match registry.split_symbols(run) {
+        Ok(symbols) => symbols,
+        Err(rest) => {
+            let start = span.end - rest.len();
+            bail!(ParserError::UnknownOperator(
+                rest.to_string(),
+                Span::new(start, span.end)
+            ))
+        }
+    };
+    let mut start = span.start;
+    for symbol in symbols {
+        let end = start + symbol.len();
+        items.push(Item::Symbol(symbol.to_string(), Span::new(start, end)));
+        start = end;
+    }
+    Ok(())
+}
+
+/// Builds the expression tree out of a flat list of terms by precedence climbing
+/// over the operators in the registry.
+struct OperatorParser<'a> {
+    items: Vec<Item>,
+    position: usize,
+    registry: &'a OperatorRegistry,
+    open_bars: usize,
+    end: usize,
+    /// Collects the errors in tolerant mode instead of failing on the first one.
+    diagnostics: Option<Vec<ParserError>>,
+}
+
+impl OperatorParser<'_> {
+    fn parse(&mut self) -> Result<Expr> {
+        let expr = self.parse_expression(0)?;
+        // The rest is dropped in tolerant mode
+        if let Some(item) = self.items.get(self.position) {
+            let err = ParserError::UnexpectedToken(self.describe(item), item.span());
+            self.recover(err, item.span())?;
+        }
+        Ok(expr)
+    }
+
+    /// Records `err` in tolerant mode and returns a placeholder for the part that
+    /// couldn't be parsed, otherwise fails with it.
+    fn recover(&mut self, err: ParserError, span: Span) -> Result<Expr> {
+        match &mut self.diagnostics {
+            Some(diagnostics) => {
+                diagnostics.push(err);
+                Ok(Expr::Error(span))
+            }
+            None => bail!(err),
+        }
+    }
+
+    fn parse_expression(&mut self, min_precedence: u32) -> Result<Expr> {
+        let mut lhs = self.parse_operand()?;
+
+        loop {
+            let (symbol, span) = match self.items.get(self.position) {
+                None => break,
+                // Inside bars a `|` after an operand always closes the pair
+                Some(Item::Bar(_)) if self.open_bars > 0 => break,
+                Some(Item::Symbol(symbol, span)) => (symbol.clone(), *span),
+                Some(_) => {
+                    if precedence::IMPLICIT_MULTIPLY < min_precedence {
+                        break;
+                    }
+                    lhs = self.implicit_multiply(lhs)?;
+                    continue;
+                }
+            };
+
+            let postfix = self.registry.postfix(&symbol).cloned();
+            let infix = self.registry.infix(&symbol).cloned();
+
+            // A symbol that is both postfix and infix, such as `%`, is only postfix
+            // when no operand follows it
+            if let Some(operator) = postfix {
+                if infix.is_none() || !self.operand_follows(self.position + 1) {
+                    if operator.precedence < min_precedence {
+                        break;
+                    }
+                    self.position += 1;
+                    lhs = apply(&operator, vec![lhs], span)?;
+                    continue;
+                }
+            }
+
+            if let Some(operator) = infix {
+                if operator.precedence < min_precedence {
+                    break;
+                }
+                if let Semantics::Relation(_) = operator.semantics {
+                    lhs = self.parse_relation(lhs, operator.precedence)?;
+                    continue;
+                }
+                self.position += 1;
+                let next_precedence = match operator.fixity {
+                    Fixity::Infix(Assoc::Right) => operator.precedence,
+                    _ => operator.precedence + 1,
                 };
+                let rhs = self.parse_expression(next_precedence)?;
+                lhs = apply(&operator, vec![lhs, rhs], span)?;
+                continue;
+            }
 
-                Ok(Expr::Constant { name, value })
+            // Prefix only operators start a new operand, as in `2√3`
+            if self.registry.prefix(&symbol).is_some() {
+                if precedence::IMPLICIT_MULTIPLY < min_precedence {
+                    break;
+                }
+                lhs = self.implicit_multiply(lhs)?;
+                continue;
             }
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
-        })
-        .map_infix(|lhs, op, rhs| {
-            let op: Result<Op> = match op.as_rule() {
-                Rule::add => Ok(Op::Add),
-                Rule::subtract => Ok(Op::Subtract),
-                Rule::multiply => Ok(Op::Multiply),
-                Rule::divide => Ok(Op::Divide),
-                Rule::modulo => Ok(Op::Modulo),
-                Rule::power => Ok(Op::Power),
-                rule => bail!(ParserError::InvalidOperator(format!("{:?}", rule))),
+
+            self.recover(ParserError::UnexpectedToken(symbol, span), span)?;
+            self.position += 1;
+        }
+
+        Ok(lhs)
+    }
+
+    /// Collects a chain of relations such as `0 < X <= 5` into a single node.
+    fn parse_relation(&mut self, lhs: Expr, precedence: u32) -> Result<Expr> {
+        let mut operands = vec![lhs];
+        let mut relations = Vec::new();
+        while let Some(Item::Symbol(symbol, _)) = self.items.get(self.position) {
+            let relation = match self.registry.infix(symbol) {
+                Some(Operator {
+                    precedence: relation_precedence,
+                    semantics: Semantics::Relation(relation),
+                    ..
+                }) if *relation_precedence == precedence => *relation,
+                _ => break,
             };
-            let op = op?;
-            Ok(Expr::BinOp {
-                lhs: Box::new(lhs?),
-                op,
-                rhs: Box::new(rhs?),
-            })
+            self.position += 1;
+            relations.push(relation);
+            operands.push(self.parse_expression(precedence + 1)?);
+        }
+
+        let span = operands[0]
+            .span()
+            .merge(operands[operands.len() - 1].span());
+        Ok(Expr::Relation {
+            operands,
+            relations,
+            span,
         })
-        .map_prefix(|op, rhs| match op.as_rule() {
-            Rule::unary_minus => Ok(Expr::UnaryMinus(Box::new(rhs?))),
-            rule => bail!(ParserError::InvalidToken(format!("{:?}", rule))),
+    }
+
+    /// Multiplies `lhs` with the operand that directly follows it.
+    fn implicit_multiply(&mut self, lhs: Expr) -> Result<Expr> {
+        // `2 3` is most likely a typo, so numbers can't be the right hand side
+        if let Some(Item::Operand {
+            expr,
+            literal: true,
+        }) = self.items.get(self.position)
+        {
+            let err = ParserError::UnexpectedToken(expr.to_string(), expr.span());
+            self.recover(err, expr.span())?;
+        }
+
+        let rhs = self.parse_expression(precedence::IMPLICIT_MULTIPLY + 1)?;
+        let span = lhs.span().merge(rhs.span());
+        Ok(Expr::BinOp {
+            lhs: Box::new(lhs),
+            op: Op::Multiply,
+            rhs: Box::new(rhs),
+            span,
         })
-        .parse(pairs)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr> {
+        let item = match self.items.get(self.position) {
+            Some(item) => item,
+            None => {
+                let span = Span::new(self.end, self.end);
+                return self.recover(ParserError::ExpectedOperand(span), span);
+            }
+        };
+
+        match item {
+            Item::Operand { expr, .. } => {
+                let expr = expr.clone();
+                self.position += 1;
+                Ok(expr)
+            }
+            Item::Bar(span) => self.parse_bars(*span),
+            // A tuple starts a lambda such as `(a, b) -> a + b` or is an open interval
+            Item::Tuple(params, span) => {
+                let (params, span) = (params.clone(), *span);
+                let operator = match self.items.get(self.position + 1) {
+                    Some(Item::Symbol(symbol, _)) => self.registry.infix(symbol).cloned(),
+                    _ => None,
+                };
+                match operator {
+                    Some(operator) if operator.semantics == Semantics::Lambda => {
+                        self.position += 2;
+                        let body = self.parse_expression(operator.precedence)?;
+                        lambda(params, body, span)
+                    }
+                    _ if params.len() == 2 => {
+                        self.position += 1;
+                        let mut params = params.into_iter();
+                        let lower = params.next().unwrap();
+                        Ok(interval(
+                            lower,
+                            params.next().unwrap(),
+                            (false, false),
+                            span,
+                        ))
+                    }
+                    _ => {
+                        self.position += 1;
+                        let tuple = self.describe(&self.items[self.position - 1]);
+                        self.recover(ParserError::UnexpectedToken(tuple, span), span)
+                    }
+                }
+            }
+            Item::Symbol(symbol, span) => {
+                let span = *span;
+                let operator = match self.registry.prefix(symbol) {
+                    Some(operator) => operator.clone(),
+                    None => return self.recover(ParserError::ExpectedOperand(span), span),
+                };
+                self.position += 1;
+                let operand = self.parse_expression(operator.precedence)?;
+                apply(&operator, vec![operand], span)
+            }
+            // `d/dX` applies to the product that follows it, as in `d/dX 2X^2 + 1`
+            Item::Derivative {
+                variable,
+                order,
+                span,
+            } => {
+                let (variable, order, span) = (variable.clone(), *order, *span);
+                self.position += 1;
+                let operand = self.parse_expression(precedence::MULTIPLICATIVE)?;
+                let span = span.merge(operand.span());
+                Ok(Expr::Derivative {
+                    expr: Box::new(operand),
+                    variable: Some(variable),
+                    order,
+                    span,
+                })
+            }
+        }
+    }
+
+    /// `||v||` is tried as a norm first and falls back to nested absolute values, so
+    /// `||-3| - 5|` still parses.
+    fn parse_bars(&mut self, open: Span) -> Result<Expr> {
+        let start = self.position;
+        if let Some(Item::Bar(second)) = self.items.get(start + 1) {
+            if second.start == open.end {
+                // The norm is only tried strictly, so a failed attempt leaves no trace
+                let diagnostics = self.diagnostics.take();
+                let norm = self.parse_pair("norm", 2);
+                self.diagnostics = diagnostics;
+                if let Ok(expr) = norm {
+                    return Ok(expr);
+                }
+                self.position = start;
+            }
+        }
+        self.parse_pair("abs", 1)
+    }
+
+    fn parse_pair(&mut self, name: &str, width: usize) -> Result<Expr> {
+        let open = self.items[self.position].span();
+        self.position += width;
+
+        self.open_bars += 1;
+        let inner = self.parse_expression(0);
+        self.open_bars -= 1;
+        let inner = inner?;
+
+        let mut close = inner.span();
+        for i in 0..width {
+            match self.items.get(self.position) {
+                Some(Item::Bar(span)) if i == 0 || span.start == close.end => close = *span,
+                _ => {
+                    self.recover(ParserError::UnclosedBar(open), open)?;
+                    break;
+                }
+            }
+            self.position += 1;
+        }
+
+        Ok(Expr::Function {
+            name: name.to_string(),
+            args: vec![inner],
+            span: open.merge(close),
+        })
+    }
+
+    fn operand_follows(&self, position: usize) -> bool {
+        match self.items.get(position) {
+            Some(Item::Operand { .. } | Item::Tuple(..) | Item::Derivative { .. }) => true,
+            Some(Item::Bar(_)) => self.open_bars == 0,
+            Some(Item::Symbol(symbol, _)) => self.registry.prefix(symbol).is_some(),
+            None => false,
+        }
+    }
+
+    fn describe(&self, item: &Item) -> String {
+        match item {
+            Item::Operand { expr, .. } => expr.to_string(),
+            Item::Symbol(symbol, _) => symbol.clone(),
+            Item::Bar(_) => "|".to_string(),
+            Item::Tuple(items, _) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                format!("({})", items.join(", "))
+            }
+            Item::Derivative {
+                variable, order: 1, ..
+            } => format!("d/d{variable}"),
+            Item::Derivative {
+                variable, order, ..
+            } => format!("d^{order}/d{variable}^{order}"),
+        }
+    }
+}
+
+/// Turns an operator and its operands into an expression node.
+fn apply(operator: &Operator, mut operands: Vec<Expr>, span: Span) -> Result<Expr> {
+    let span = operands
+        .iter()
+        .fold(span, |span, operand| span.merge(operand.span()));
+    let arity = match operator.semantics {
+        Semantics::Binary(_) | Semantics::Relation(_) | Semantics::Lambda => 2,
+        Semantics::Logic(_) | Semantics::Set(_) => 2,
+        Semantics::Unary(_) | Semantics::Negate | Semantics::Not => 1,
+        Semantics::Function(_) | Semantics::Custom(_) => operands.len(),
+    };
+    if arity != operands.len() {
+        bail!(ParserError::InvalidOperator(operator.symbol.clone(), span));
+    }
+
+    Ok(match &operator.semantics {
+        Semantics::Binary(op) => {
+            let rhs = operands.pop().unwrap();
+            let lhs = operands.pop().unwrap();
+            Expr::BinOp {
+                lhs: Box::new(lhs),
+                op: *op,
+                rhs: Box::new(rhs),
+                span,
+            }
+        }
+        Semantics::Unary(op) => Expr::UnaryOp {
+            op: *op,
+            operand: Box::new(operands.remove(0)),
+            span,
+        },
+        Semantics::Negate => Expr::UnaryMinus(Box::new(operands.remove(0)), span),
+        Semantics::Not => Expr::Not(Box::new(operands.remove(0)), span),
+        Semantics::Logic(op) => {
+            let rhs = operands.pop().unwrap();
+            let lhs = operands.pop().unwrap();
+            Expr::Logic {
+                lhs: Box::new(lhs),
+                op: *op,
+                rhs: Box::new(rhs),
+                span,
+            }
+        }
+        Semantics::Set(op) => {
+            let rhs = as_set(operands.pop().unwrap());
+            let lhs = match op {
+                SetOp::Element => operands.pop().unwrap(),
+                _ => as_set(operands.pop().unwrap()),
+            };
+            Expr::SetOp {
+                lhs: Box::new(lhs),
+                op: *op,
+                rhs: Box::new(rhs),
+                span,
+            }
+        }
+        Semantics::Relation(relation) => Expr::Relation {
+            operands,
+            relations: vec![*relation],
+            span,
+        },
+        Semantics::Function(name) => Expr::Function {
+            name: name.clone(),
+            args: operands,
+            span,
+        },
+        Semantics::Lambda => {
+            let body = operands.pop().unwrap();
+            return lambda(operands, body, span);
+        }
+        Semantics::Custom(function) => Expr::Operator {
+            symbol: operator.symbol.clone(),
+            fixity: operator.fixity,
+            operands,
+            function: function.clone(),
+            span,
+        },
+    })
+}
+
+/// Whether the lower and upper end of an interval such as `[0, 1)` are closed.
+fn interval_ends(text: &str) -> (bool, bool) {
+    (text.starts_with('['), text.ends_with(']'))
+}
+
+fn interval(lower: Expr, upper: Expr, closed: (bool, bool), span: Span) -> Expr {
+    Expr::Interval {
+        lower: Box::new(lower),
+        upper: Box::new(upper),
+        lower_closed: closed.0,
+        upper_closed: closed.1,
+        span,
+    }
+}
+
+/// A list of two items next to a set operator, as in `X in [0, 1]`, is the closed
+/// interval rather than the set of both ends.
+fn as_set(expr: Expr) -> Expr {
+    match expr {
+        Expr::List { mut items, span } if items.len() == 2 => {
+            let upper = items.pop().unwrap();
+            interval(items.pop().unwrap(), upper, (true, true), span)
+        }
+        expr => expr,
+    }
+}
+
+/// Builds a lambda, whose parameters have to be distinct variables.
+fn lambda(params: Vec<Expr>, body: Expr, span: Span) -> Result<Expr> {
+    let span = params.iter().fold(span.merge(body.span()), |span, param| {
+        span.merge(param.span())
+    });
+    let mut names: Vec<String> = Vec::new();
+    for param in params {
+        match param.variable() {
+            Some(variable) if !names.iter().any(|name| name == variable) => {
+                names.push(variable.to_string())
+            }
+            _ => bail!(ParserError::InvalidParameter(
+                param.to_string(),
+                param.span()
+            )),
+        }
+    }
+    Ok(Expr::Lambda {
+        params: names,
+        body: Box::new(body),
+        span,
+    })
+}
+
+fn parse_expr(expr: Pair<Rule>, registry: &OperatorRegistry) -> Result<Expr> {
+    let mut items = Vec::new();
+    push_items(&mut StrictParser { registry }, &mut items, expr)?;
+    build_expr(items, registry)
+}
+
+pub(super) fn build_expr(items: Vec<Item>, registry: &OperatorRegistry) -> Result<Expr> {
+    let end = items.last().map_or(0, |item| item.span().end);
+    OperatorParser {
+        items,
+        position: 0,
+        registry,
+        open_bars: 0,
+        end,
+        diagnostics: None,
+    }
+    .parse()
+}
+
+/// Like [`build_expr`], but stands in [`Expr::Error`] for missing operands and adds
+/// the errors to `diagnostics`. `end` is where the input ends.
+pub(super) fn build_expr_tolerant(
+    items: Vec<Item>,
+    registry: &OperatorRegistry,
+    end: usize,
+    diagnostics: &mut Vec<ParserError>,
+) -> Expr {
+    let mut parser = OperatorParser {
+        items,
+        position: 0,
+        registry,
+        open_bars: 0,
+        end,
+        diagnostics: Some(Vec::new()),
+    };
+    let expr = parser.parse();
+    diagnostics.append(parser.diagnostics.as_mut().unwrap());
+    match expr {
+        Ok(expr) => expr,
+        Err(err) => {
+            let span = Span::new(0, end);
+            match err.downcast::<ParserError>() {
+                Ok(err) => diagnostics.push(err),
+                Err(err) => diagnostics.push(ParserError::Syntax(err.to_string(), span)),
+            }
+            Expr::Error(span)
+        }
+    }
+}
+
+/// Converts a pest error into a [`ParserError`] pointing at the offending character.
+pub(super) fn syntax_error<R: pest::RuleType>(
+    expression: &str,
+    err: pest::error::Error<R>,
+) -> ParserError {
+    let span = match err.location {
+        InputLocation::Pos(pos) => {
+            let len = expression[pos..].chars().next().map_or(0, char::len_utf8);
+            Span::new(pos, pos + len)
+        }
+        InputLocation::Span((start, end)) => Span::new(start, end),
+    };
+    ParserError::Syntax(err.variant.message().to_string(), span)
 }
 
 pub fn parse(expression: &str) -> Result<Expr> {
-    let mut pairs = CalculatorParser::parse(Rule::equation, expression)?;
-    parse_expr(pairs.next().unwrap().into_inner())
+    parse_with_config(expression, &parse_config())
 }
 
-pub fn parse_equation(expression: &str) -> Result<Expr> {
-    if !expression.contains("=") {
-        bail!(ParserError::NoEquals);
+/// Parses `expression` with its own separators instead of the global ones.
+pub fn parse_with_config(expression: &str, config: &ParseConfig) -> Result<Expr> {
+    parse_with_operators(&config.normalize(expression), &operators())
+}
+
+/// Parses `expression` with a custom set of operators instead of the global registry.
+pub fn parse_with_operators(expression: &str, registry: &OperatorRegistry) -> Result<Expr> {
+    let mut pairs = match CalculatorParser::parse(Rule::equation, expression) {
+        Ok(pairs) => pairs,
+        Err(err) => bail!(syntax_error(expression, err)),
+    };
+    parse_expr(pairs.next().unwrap(), registry)
+}
+
+fn parse_assignment(assignment: Pair<Rule>, registry: &OperatorRegistry) -> Result<Statement> {
+    let span = Span::from(assignment.as_span());
+    let mut pairs = assignment.into_inner();
+    let name = pairs.next().unwrap();
+    // `XY` would read back as the product `X*Y`
+    if !matches!(resolve_name(name.as_str()), Name::Variable) || is_product(name.as_str()) {
+        bail!(ParserError::InvalidAssignment(
+            name.as_str().to_string(),
+            name.as_span().into()
+        ));
     }
 
-    let expression: Vec<&str> = expression.split("=").collect();
-    if expression.len() != 2 {
-        bail!(ParserError::EqualsCount);
+    Ok(Statement::Assignment {
+        name: name.as_str().to_string(),
+        value: parse_expr(pairs.next().unwrap(), registry)?,
+        span,
+    })
+}
+
+fn parse_definition(definition: Pair<Rule>, registry: &OperatorRegistry) -> Result<Statement> {
+    let span = Span::from(definition.as_span());
+    let mut name = String::new();
+    let mut name_span = span;
+    let mut params: Vec<String> = Vec::new();
+    let mut body = None;
+    for pair in definition.into_inner() {
+        match pair.as_rule() {
+            Rule::identifier => {
+                name = pair.as_str().to_string();
+                name_span = pair.as_span().into();
+            }
+            Rule::parameters => {
+                for param in pair.into_inner() {
+                    // Parameters shadow variables, but not constants or other parameters
+                    let text = param.as_str().to_string();
+                    if !matches!(resolve_name(&text), Name::Variable)
+                        || is_product(&text)
+                        || params.contains(&text)
+                    {
+                        bail!(ParserError::InvalidAssignment(text, param.as_span().into()));
+                    }
+                    params.push(text);
+                }
+            }
+            Rule::expr => body = Some(parse_expr(pair, registry)?),
+            rule => bail!(ParserError::InvalidToken(
+                format!("{:?}", rule),
+                pair.as_span().into()
+            )),
+        }
     }
 
-    let lhs = parse(expression[0])?;
-    let rhs = parse(expression[1])?;
+    if !matches!(resolve_name(&name), Name::Variable) {
+        bail!(ParserError::InvalidAssignment(name, name_span));
+    }
 
-    Ok(Expr::BinOp {
-        lhs: Box::new(lhs),
-        op: Op::Equals,
-        rhs: Box::new(rhs),
+    Ok(Statement::Definition {
+        name,
+        params,
+        body: body.unwrap(),
+        span,
     })
 }
+
+/// Parses an input holding several statements separated by `;` or line breaks.
+pub fn parse_statements(expression: &str) -> Result<Vec<Statement>> {
+    parse_statements_with_config(expression, &parse_config())
+}
+
+/// Parses several statements with their own separators instead of the global ones.
+pub fn parse_statements_with_config(
+    expression: &str,
+    config: &ParseConfig,
+) -> Result<Vec<Statement>> {
+    let expression = config.normalize(expression);
+    let registry = operators();
+    let pairs = match CalculatorParser::parse(Rule::statements, &expression) {
+        Ok(pairs) => pairs,
+        Err(err) => bail!(syntax_error(&expression, err)),
+    };
+
+    let mut statements = Vec::new();
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::assignment => statements.push(parse_assignment(pair, &registry)?),
+            Rule::definition => statements.push(parse_definition(pair, &registry)?),
+            Rule::expr => statements.push(Statement::Expression(parse_expr(pair, &registry)?)),
+            _ => (),
+        }
+    }
+    Ok(statements)
+}
+
+/// Location of the relation symbol between operands `index` and `index + 1`.
+fn relation_span(expression: &str, operands: &[Expr], index: usize) -> Span {
+    let start = operands[index].span().end;
+    let end = operands[index + 1].span().start;
+    let gap = &expression[start..end];
+    let leading = gap.len() - gap.trim_start().len();
+    let trailing = gap.len() - gap.trim_end().len();
+    Span::new(start + leading, end - trailing)
+}
+
+/// Parses an equation, a relation with exactly one `=`.
+pub fn parse_equation(expression: &str) -> Result<Expr> {
+    let span = Span::new(0, expression.len());
+    let expr = parse(expression)?;
+    let (operands, relations) = match &expr {
+        Expr::Relation {
+            operands,
+            relations,
+            ..
+        } if relations.contains(&Relation::Equal) => (operands, relations),
+        _ => bail!(ParserError::NoEquals(span)),
+    };
+
+    let equals = relations
+        .iter()
+        .position(|relation| *relation == Relation::Equal)
+        .unwrap();
+    for (index, relation) in relations.iter().enumerate() {
+        if index == equals {
+            continue;
+        }
+        let span = relation_span(expression, operands, index);
+        match relation {
+            Relation::Equal => bail!(ParserError::EqualsCount(span)),
+            _ => bail!(ParserError::InvalidOperator(
+                expression[span.range()].to_string(),
+                span
+            )),
+        }
+    }
+
+    Ok(expr)
+}
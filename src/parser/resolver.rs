@@ -0,0 +1,31 @@
+use crate::math::{CONSTANTS_DATABASE, FUNCTIONS_DATABASE};
+
+/// What an identifier refers to once the expression has been parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Name {
+    Function,
+    Constant(f64),
+    /// `true` or `false`.
+    Bool(bool),
+    Variable,
+}
+
+/// Resolves an identifier against the known constants and functions. Anything
+/// that is neither is treated as a user variable.
+pub fn resolve_name(name: &str) -> Name {
+    match name {
+        "true" => return Name::Bool(true),
+        "false" => return Name::Bool(false),
+        _ => {}
+    }
+
+    if let Some(value) = CONSTANTS_DATABASE.get(name) {
+        return Name::Constant(*value);
+    }
+
+    if FUNCTIONS_DATABASE.contains(name) {
+        return Name::Function;
+    }
+
+    Name::Variable
+}
@@ -0,0 +1,36 @@
+use std::ops::Range;
+
+/// Byte range of an expression node or an error in the parsed source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    /// Smallest span that covers both `self` and `other`.
+    pub fn merge(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Converts the byte offsets into character offsets within `source`.
+    pub fn char_range(&self, source: &str) -> Range<usize> {
+        let start = source[..self.start].chars().count();
+        let end = start + source[self.start..self.end].chars().count();
+        start..end
+    }
+}
+
+impl From<pest::Span<'_>> for Span {
+    fn from(span: pest::Span) -> Self {
+        Span::new(span.start(), span.end())
+    }
+}
@@ -1,27 +1,305 @@
+use std::fmt;
+
+use crate::math::nearly_equal;
+
+use super::{Fixity, OperatorFn, Rational, Span};
+
+/// Expression tree. Every node carries the span of the source it was parsed from.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
-    Number(f64),
-    UnaryMinus(Box<Expr>),
+    Number(f64, Span),
+    /// `true` or `false`.
+    Bool(bool, Span),
+    UnaryMinus(Box<Expr>, Span),
+    /// Logical negation written `not X`.
+    Not(Box<Expr>, Span),
+    UnaryOp {
+        op: UnaryOp,
+        operand: Box<Expr>,
+        span: Span,
+    },
     BinOp {
         lhs: Box<Expr>,
         op: Op,
         rhs: Box<Expr>,
+        span: Span,
     },
     Function {
         name: String,
-        args: Vec<Box<Expr>>,
+        args: Vec<Expr>,
+        span: Span,
     },
+    /// A product of variables such as `3X^2Y`. The factors are sorted by variable,
+    /// each variable appears once and its exponent is exact.
     Monomial {
         coefficient: f64,
-        variable: String,
-        exponent: f64,
+        factors: Vec<(String, Rational)>,
+        span: Span,
     },
     Constant {
         name: String,
         value: f64,
+        span: Span,
+    },
+    /// A chain of comparisons such as `0 < X <= 5`, with one relation between each
+    /// pair of neighbouring operands.
+    Relation {
+        operands: Vec<Expr>,
+        relations: Vec<Relation>,
+        span: Span,
+    },
+    /// `a and b`, `a or b`, `a xor b` or `a implies b`.
+    Logic {
+        lhs: Box<Expr>,
+        op: Logic,
+        rhs: Box<Expr>,
+        span: Span,
+    },
+    /// A list literal such as `[1, 2, 3]`.
+    List {
+        items: Vec<Expr>,
+        span: Span,
+    },
+    /// `[0, 1)`, `(-inf, 3]` or the open `(0, 1)`.
+    Interval {
+        lower: Box<Expr>,
+        upper: Box<Expr>,
+        lower_closed: bool,
+        upper_closed: bool,
+        span: Span,
+    },
+    /// A finite set such as `{1, 2, 3}`.
+    Set {
+        items: Vec<Expr>,
+        span: Span,
+    },
+    /// `A union B`, `A intersect B`, `A \ B` or `X in A`.
+    SetOp {
+        lhs: Box<Expr>,
+        op: SetOp,
+        rhs: Box<Expr>,
+        span: Span,
+    },
+    /// Element of a list such as `v[2]`. Indices start from one.
+    Index {
+        target: Box<Expr>,
+        indices: Vec<Expr>,
+        span: Span,
+    },
+    /// `piecewise((value, condition), ..., otherwise)` takes the value of the first
+    /// case whose condition holds, or the fallback. `if(c, a, b)` is parsed into one.
+    Piecewise {
+        cases: Vec<(Expr, Expr)>,
+        otherwise: Option<Box<Expr>>,
+        span: Span,
+    },
+    /// Anonymous function such as `X -> X^2` or `(a, b) -> a + b`.
+    Lambda {
+        params: Vec<String>,
+        body: Box<Expr>,
+        span: Span,
+    },
+    /// `sum(k, 1, n, body)` or `prod(k, 1, n, body)` over the whole numbers from
+    /// `lower` to `upper`, which may be infinite. `variable` is only bound in `body`.
+    Series {
+        kind: Series,
+        variable: String,
+        lower: Box<Expr>,
+        upper: Box<Expr>,
+        body: Box<Expr>,
+        span: Span,
+    },
+    /// An unevaluated derivative of `expr` of the given order, written `d/dX (expr)`,
+    /// `d^2/dX^2 (expr)` or `diff(expr, X, 2)`. Prime notation such as `f''(a)` has no
+    /// variable, it differentiates the function called in `expr` and calls the result.
+    Derivative {
+        expr: Box<Expr>,
+        variable: Option<String>,
+        order: u32,
+        span: Span,
+    },
+    /// Stands in for a part of the input that couldn't be parsed. Only produced by
+    /// [`parse_tolerant`](super::parse_tolerant).
+    Error(Span),
+    /// An operator registered with [`Semantics::Custom`](super::Semantics::Custom).
+    Operator {
+        symbol: String,
+        fixity: Fixity,
+        operands: Vec<Expr>,
+        function: OperatorFn,
+        span: Span,
     },
 }
 
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Number(_, span)
+            | Expr::Bool(_, span)
+            | Expr::Error(span)
+            | Expr::UnaryMinus(_, span)
+            | Expr::Not(_, span)
+            | Expr::UnaryOp { span, .. }
+            | Expr::BinOp { span, .. }
+            | Expr::Function { span, .. }
+            | Expr::Monomial { span, .. }
+            | Expr::Constant { span, .. }
+            | Expr::Relation { span, .. }
+            | Expr::Logic { span, .. }
+            | Expr::Interval { span, .. }
+            | Expr::Set { span, .. }
+            | Expr::SetOp { span, .. }
+            | Expr::List { span, .. }
+            | Expr::Index { span, .. }
+            | Expr::Piecewise { span, .. }
+            | Expr::Series { span, .. }
+            | Expr::Derivative { span, .. }
+            | Expr::Lambda { span, .. }
+            | Expr::Operator { span, .. } => *span,
+        }
+    }
+
+    pub fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Expr::Number(_, span)
+            | Expr::Bool(_, span)
+            | Expr::Error(span)
+            | Expr::UnaryMinus(_, span)
+            | Expr::Not(_, span)
+            | Expr::UnaryOp { span, .. }
+            | Expr::BinOp { span, .. }
+            | Expr::Function { span, .. }
+            | Expr::Monomial { span, .. }
+            | Expr::Constant { span, .. }
+            | Expr::Relation { span, .. }
+            | Expr::Logic { span, .. }
+            | Expr::Interval { span, .. }
+            | Expr::Set { span, .. }
+            | Expr::SetOp { span, .. }
+            | Expr::List { span, .. }
+            | Expr::Index { span, .. }
+            | Expr::Piecewise { span, .. }
+            | Expr::Series { span, .. }
+            | Expr::Derivative { span, .. }
+            | Expr::Lambda { span, .. }
+            | Expr::Operator { span, .. } => span,
+        }
+    }
+
+    /// Whether both trees are the same expression, wherever they were written. `==`
+    /// also compares the spans.
+    pub fn same_shape(&self, other: &Expr) -> bool {
+        fn clear(expr: &mut Expr) {
+            *expr.span_mut() = Span::default();
+            expr.children_mut().into_iter().for_each(clear);
+        }
+
+        let (mut lhs, mut rhs) = (self.clone(), other.clone());
+        clear(&mut lhs);
+        clear(&mut rhs);
+        lhs == rhs
+    }
+
+    /// The direct subexpressions of this node.
+    fn children_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            Expr::Number(..)
+            | Expr::Bool(..)
+            | Expr::Error(_)
+            | Expr::Monomial { .. }
+            | Expr::Constant { .. } => Vec::new(),
+            Expr::UnaryMinus(operand, _)
+            | Expr::Not(operand, _)
+            | Expr::UnaryOp { operand, .. }
+            | Expr::Lambda { body: operand, .. }
+            | Expr::Derivative { expr: operand, .. } => vec![operand.as_mut()],
+            Expr::BinOp { lhs, rhs, .. }
+            | Expr::Logic { lhs, rhs, .. }
+            | Expr::SetOp { lhs, rhs, .. }
+            | Expr::Interval {
+                lower: lhs,
+                upper: rhs,
+                ..
+            } => vec![lhs.as_mut(), rhs.as_mut()],
+            Expr::Function { args: items, .. }
+            | Expr::Relation {
+                operands: items, ..
+            }
+            | Expr::List { items, .. }
+            | Expr::Set { items, .. }
+            | Expr::Operator {
+                operands: items, ..
+            } => items.iter_mut().collect(),
+            Expr::Index {
+                target, indices, ..
+            } => std::iter::once(target.as_mut())
+                .chain(indices.iter_mut())
+                .collect(),
+            Expr::Piecewise {
+                cases, otherwise, ..
+            } => cases
+                .iter_mut()
+                .flat_map(|(value, condition)| [value, condition])
+                .chain(otherwise.iter_mut().map(|otherwise| otherwise.as_mut()))
+                .collect(),
+            Expr::Series {
+                lower, upper, body, ..
+            } => vec![lower.as_mut(), upper.as_mut(), body.as_mut()],
+        }
+    }
+
+    /// The name of a lone variable such as `X`, without a coefficient or exponent.
+    pub fn variable(&self) -> Option<&str> {
+        match self {
+            Expr::Monomial {
+                coefficient,
+                factors,
+                ..
+            } if *coefficient == 1.0 => match factors.as_slice() {
+                [(variable, exponent)] if *exponent == Rational::ONE => Some(variable),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Sorts the factors of a monomial by variable and adds up the exponents of each
+/// variable, so `YX^2X` has the factors `X^3` and `Y`. `None` when an exponent
+/// doesn't fit.
+pub(crate) fn merge_factors(
+    mut factors: Vec<(String, Rational)>,
+) -> Option<Vec<(String, Rational)>> {
+    factors.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut merged: Vec<(String, Rational)> = Vec::new();
+    for (variable, exponent) in factors {
+        match merged.last_mut() {
+            Some((last, sum)) if *last == variable => *sum = sum.checked_add(exponent)?,
+            _ => merged.push((variable, exponent)),
+        }
+    }
+    Some(merged)
+}
+
+/// A single statement of an input holding several of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `name := value`
+    Assignment {
+        name: String,
+        value: Expr,
+        span: Span,
+    },
+    /// `name(params) := body`
+    Definition {
+        name: String,
+        params: Vec<String>,
+        body: Expr,
+        span: Span,
+    },
+    Expression(Expr),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Op {
     Add,
@@ -30,7 +308,83 @@ pub enum Op {
     Divide,
     Modulo,
     Power,
-    Equals,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relation {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Relation {
+    /// Whether `lhs` and `rhs` are related, treating nearly equal values as equal so
+    /// that rounding errors don't decide the result.
+    pub fn holds(self, lhs: f64, rhs: f64) -> bool {
+        let equal = nearly_equal(lhs, rhs);
+        match self {
+            Relation::Equal => equal,
+            Relation::NotEqual => !equal,
+            Relation::Less => lhs < rhs && !equal,
+            Relation::LessEqual => lhs < rhs || equal,
+            Relation::Greater => lhs > rhs && !equal,
+            Relation::GreaterEqual => lhs > rhs || equal,
+        }
+    }
+
+    /// Whether the booleans `lhs` and `rhs` are related, or nothing for an ordering
+    /// such as `<` that booleans don't have.
+    pub fn holds_bool(self, lhs: bool, rhs: bool) -> Option<bool> {
+        match self {
+            Relation::Equal => Some(lhs == rhs),
+            Relation::NotEqual => Some(lhs != rhs),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Logic {
+    And,
+    Or,
+    Xor,
+    Implies,
+}
+
+impl Logic {
+    pub fn apply(self, lhs: bool, rhs: bool) -> bool {
+        match self {
+            Logic::And => lhs && rhs,
+            Logic::Or => lhs || rhs,
+            Logic::Xor => lhs != rhs,
+            Logic::Implies => !lhs || rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+    /// Membership of a number, which gives a boolean rather than a set.
+    Element,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Series {
+    Sum,
+    Product,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Factorial,
+    DoubleFactorial,
+    Percent,
 }
 
 pub trait Optimize {
@@ -39,13 +393,24 @@ pub trait Optimize {
     fn optimize_equation(self) -> Expr;
 }
 
-impl ToString for Expr {
-    fn to_string(&self) -> String {
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut out = String::new();
         match self {
-            Expr::Number(val) => out.push_str(&val.to_string()),
-            Expr::UnaryMinus(expr) => out.push_str(&format!("-({})", expr.to_string())),
-            Expr::BinOp { lhs, op, rhs } => {
+            Expr::Number(val, _) => out.push_str(&val.to_string()),
+            Expr::Bool(val, _) => out.push_str(&val.to_string()),
+            Expr::UnaryMinus(expr, _) => out.push_str(&format!("-({expr})")),
+            Expr::Not(expr, _) => out.push_str(&format!("not({expr})")),
+            Expr::UnaryOp { op, operand, .. } => {
+                let op = match op {
+                    UnaryOp::Factorial => "!",
+                    UnaryOp::DoubleFactorial => "!!",
+                    UnaryOp::Percent => "%",
+                };
+
+                out.push_str(&format!("({operand}){op}"));
+            }
+            Expr::BinOp { lhs, op, rhs, .. } => {
                 let lhs = lhs.to_string();
                 let rhs = rhs.to_string();
                 let op = match op {
@@ -55,14 +420,43 @@ impl ToString for Expr {
                     Op::Divide => '/',
                     Op::Modulo => '%',
                     Op::Power => '^',
-                    Op::Equals => '=',
                 };
 
                 out.push_str(&format!("({lhs}{op}{rhs})"));
             }
-            Expr::Function { name, args } => {
+            Expr::Relation {
+                operands,
+                relations,
+                ..
+            } => {
+                out.push('(');
+                out.push_str(&operands[0].to_string());
+                for (relation, operand) in relations.iter().zip(&operands[1..]) {
+                    let relation = match relation {
+                        Relation::Equal => '=',
+                        Relation::NotEqual => '≠',
+                        Relation::Less => '<',
+                        Relation::LessEqual => '≤',
+                        Relation::Greater => '>',
+                        Relation::GreaterEqual => '≥',
+                    };
+                    out.push_str(&format!("{relation}{operand}"));
+                }
+                out.push(')');
+            }
+            Expr::Logic { lhs, op, rhs, .. } => {
+                // Keyword operators need spaces to be parsed back
+                let op = match op {
+                    Logic::And => "and",
+                    Logic::Or => "or",
+                    Logic::Xor => "xor",
+                    Logic::Implies => "implies",
+                };
+                out.push_str(&format!("({lhs} {op} {rhs})"));
+            }
+            Expr::Function { name, args, .. } => {
                 let args = args
-                    .into_iter()
+                    .iter()
                     .map(|arg| arg.to_string())
                     .collect::<Vec<String>>()
                     .join(", ");
@@ -70,11 +464,116 @@ impl ToString for Expr {
             }
             Expr::Monomial {
                 coefficient,
+                factors,
+                ..
+            } => {
+                out.push_str(&coefficient.to_string());
+                for (variable, exponent) in factors {
+                    out.push_str(&format!("{variable}^({exponent})"));
+                }
+            }
+            Expr::Constant { name, .. } => out.push_str(name),
+            Expr::Error(_) => out.push('?'),
+            Expr::List { items, .. } => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                out.push_str(&format!("[{}]", items.join(", ")));
+            }
+            Expr::Interval {
+                lower,
+                upper,
+                lower_closed,
+                upper_closed,
+                ..
+            } => {
+                let open = if *lower_closed { '[' } else { '(' };
+                let close = if *upper_closed { ']' } else { ')' };
+                out.push_str(&format!("{open}{lower}, {upper}{close}"));
+            }
+            Expr::Set { items, .. } => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                out.push_str(&format!("{{{}}}", items.join(", ")));
+            }
+            Expr::SetOp { lhs, op, rhs, .. } => {
+                let op = match op {
+                    SetOp::Union => '∪',
+                    SetOp::Intersection => '∩',
+                    SetOp::Difference => '\\',
+                    SetOp::Element => '∈',
+                };
+                out.push_str(&format!("({lhs} {op} {rhs})"));
+            }
+            Expr::Index {
+                target, indices, ..
+            } => {
+                let indices: Vec<String> = indices.iter().map(|index| index.to_string()).collect();
+                out.push_str(&format!("({target})[{}]", indices.join(", ")));
+            }
+            Expr::Piecewise {
+                cases, otherwise, ..
+            } => {
+                let mut args: Vec<String> = cases
+                    .iter()
+                    .map(|(value, condition)| format!("({value}, {condition})"))
+                    .collect();
+                args.extend(otherwise.iter().map(|otherwise| otherwise.to_string()));
+                out.push_str(&format!("piecewise({})", args.join(", ")));
+            }
+            Expr::Lambda { params, body, .. } => {
+                out.push_str(&format!("(({}) -> {body})", params.join(", ")))
+            }
+            Expr::Series {
+                kind,
                 variable,
-                exponent,
-            } => out.push_str(&format!("{coefficient}{variable}^({exponent})")),
-            Expr::Constant { name, .. } => out.push_str(&name),
+                lower,
+                upper,
+                body,
+                ..
+            } => {
+                let name = match kind {
+                    Series::Sum => "sum",
+                    Series::Product => "prod",
+                };
+                out.push_str(&format!("{name}({variable}, {lower}, {upper}, {body})"));
+            }
+            Expr::Derivative {
+                expr,
+                variable: Some(variable),
+                order,
+                ..
+            } => match order {
+                1 => out.push_str(&format!("diff({expr}, {variable})")),
+                order => out.push_str(&format!("diff({expr}, {variable}, {order})")),
+            },
+            // The primes go between the name and the arguments, as in `f''(X)`
+            Expr::Derivative {
+                expr,
+                variable: None,
+                order,
+                ..
+            } => {
+                let call = expr.to_string();
+                let (name, args) = call.split_at(call.find('(').unwrap_or(call.len()));
+                out.push_str(&format!("{name}{}{args}", "'".repeat(*order as usize)));
+            }
+            Expr::Operator {
+                symbol,
+                fixity,
+                operands,
+                ..
+            } => {
+                // Keyword operators need spaces to be parsed back
+                let symbol = match symbol.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    true => format!(" {symbol} "),
+                    false => symbol.to_owned(),
+                };
+                let operands: Vec<String> = operands.iter().map(|arg| arg.to_string()).collect();
+                match fixity {
+                    Fixity::Infix(_) => out.push_str(&format!("({})", operands.join(&symbol))),
+                    Fixity::Prefix => out.push_str(&format!("{}({})", symbol.trim(), operands[0])),
+                    Fixity::Postfix => out.push_str(&format!("({}){}", operands[0], symbol.trim())),
+                }
+            }
         }
-        return out;
+        write!(f, "{out}")
     }
 }
@@ -0,0 +1,212 @@
+use pest::iterators::Pair;
+use pest::Parser;
+use wasm_bindgen::prelude::*;
+
+use super::config::parse_config;
+use super::operators::operators;
+use super::parser::{is_keyword_call, CalculatorParser, Rule};
+use super::{resolve_name, Name, OperatorRegistry, ParseConfig, Span};
+
+/// What a token is, for syntax highlighting.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Number,
+    Variable,
+    Constant,
+    /// The name of a function, called or not.
+    Function,
+    /// Operators including keyword operators, absolute value bars, the commas
+    /// between arguments and the `:=` and `;` of statements.
+    Operator,
+    /// Parentheses, square brackets and braces.
+    Paren,
+    /// Characters that start no term, unknown operators and unmatched brackets.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Tokens of an expression in source order, without the whitespace between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenStream {
+    pub tokens: Vec<Token>,
+    /// Spans of the opening and closing bracket of every matching pair, in the order
+    /// they are closed.
+    pub brackets: Vec<(Span, Span)>,
+}
+
+/// Walks the pairs of the tolerant `loose_statements` rule, which accepts any input.
+struct Tokenizer<'a> {
+    source: &'a str,
+    registry: &'a OperatorRegistry,
+    tokens: Vec<Token>,
+    brackets: Vec<(Span, Span)>,
+    /// The closing character expected by every bracket still open, and its token index.
+    open: Vec<(char, usize)>,
+}
+
+impl Tokenizer<'_> {
+    fn push(&mut self, kind: TokenKind, start: usize, end: usize) {
+        self.tokens.push(Token {
+            kind,
+            span: Span::new(start, end),
+        });
+    }
+
+    fn walk(&mut self, pair: Pair<Rule>) {
+        let span = pair.as_span();
+        let (start, end) = (span.start(), span.end());
+        match pair.as_rule() {
+            Rule::number | Rule::signed | Rule::superscript => {
+                self.push(TokenKind::Number, start, end)
+            }
+            Rule::identifier => {
+                let kind = match resolve_name(pair.as_str()) {
+                    Name::Variable => TokenKind::Variable,
+                    Name::Constant(_) | Name::Bool(_) => TokenKind::Constant,
+                    Name::Function => TokenKind::Function,
+                };
+                self.push(kind, start, end);
+            }
+            Rule::monomial if self.registry.is_keyword(pair.as_str()) => {
+                self.push(TokenKind::Operator, start, end)
+            }
+            // Any name that is called, including user-defined functions, is a function
+            Rule::loose_function if is_keyword_call(&pair, self.registry) => {
+                let name = pair.clone().into_inner().next().unwrap().as_span();
+                self.push(TokenKind::Operator, start, name.end());
+                self.walk_inner(pair.into_inner().skip(1), name.end(), end);
+            }
+            Rule::loose_function => {
+                let mut inner = pair.into_inner();
+                let name = inner.next().unwrap();
+                let kind = match resolve_name(name.as_str()) {
+                    Name::Constant(_) => TokenKind::Constant,
+                    _ => TokenKind::Function,
+                };
+                let name_end = name.as_span().end();
+                self.push(kind, start, name_end);
+                self.walk_inner(inner, name_end, end);
+            }
+            Rule::operator => {
+                let run = pair.as_str();
+                match self.registry.split_symbols(run) {
+                    Ok(_) => self.push(TokenKind::Operator, start, end),
+                    Err(rest) => {
+                        let known = end - rest.len();
+                        if known > start {
+                            self.push(TokenKind::Operator, start, known);
+                        }
+                        self.push(TokenKind::Error, known, end);
+                    }
+                }
+            }
+            // The brackets of `[0, 1)` don't match each other
+            Rule::loose_interval => {
+                let closing = if pair.as_str().starts_with('[') {
+                    ')'
+                } else {
+                    ']'
+                };
+                self.open.push((closing, self.tokens.len()));
+                self.push(TokenKind::Paren, start, start + 1);
+                self.walk_inner(pair.into_inner(), start + 1, end);
+            }
+            Rule::bar | Rule::primes | Rule::derivative | Rule::define | Rule::semicolon => {
+                self.push(TokenKind::Operator, start, end)
+            }
+            Rule::close_paren | Rule::close_bracket | Rule::close_brace => {
+                self.literals(start, end)
+            }
+            Rule::stray => self.push(TokenKind::Error, start, end),
+            _ => self.walk_inner(pair.into_inner(), start, end),
+        }
+    }
+
+    /// Walks the children of a pair, classifying the literal characters between them.
+    fn walk_inner<'i>(
+        &mut self,
+        pairs: impl Iterator<Item = Pair<'i, Rule>>,
+        start: usize,
+        end: usize,
+    ) {
+        let mut position = start;
+        for pair in pairs {
+            let span = pair.as_span();
+            self.literals(position, span.start());
+            position = span.end();
+            self.walk(pair);
+        }
+        self.literals(position, end);
+    }
+
+    /// Classifies the characters that the grammar matches without a rule of their own,
+    /// such as brackets, commas and the `^` and `/` of an exponent.
+    fn literals(&mut self, start: usize, end: usize) {
+        for (offset, c) in self.source[start..end].char_indices() {
+            let (start, end) = (start + offset, start + offset + c.len_utf8());
+            match c {
+                '(' | '[' | '{' => {
+                    let closing = match c {
+                        '(' => ')',
+                        '[' => ']',
+                        _ => '}',
+                    };
+                    self.open.push((closing, self.tokens.len()));
+                    self.push(TokenKind::Paren, start, end);
+                }
+                ')' | ']' | '}' => match self.open.last() {
+                    Some(&(closing, index)) if closing == c => {
+                        self.open.pop();
+                        let span = Span::new(start, end);
+                        self.brackets.push((self.tokens[index].span, span));
+                        self.push(TokenKind::Paren, start, end);
+                    }
+                    _ => self.push(TokenKind::Error, start, end),
+                },
+                ',' | '^' | '/' => self.push(TokenKind::Operator, start, end),
+                c if c.is_whitespace() => {}
+                _ => self.push(TokenKind::Error, start, end),
+            }
+        }
+    }
+}
+
+/// Splits the statements in `expression` into classified tokens and pairs up their
+/// brackets, using the same grammar as the parser. Never fails, input that doesn't parse gets
+/// [`TokenKind::Error`] tokens and brackets that are never closed are errors too.
+pub fn tokenize(expression: &str) -> TokenStream {
+    tokenize_with_config(expression, &parse_config())
+}
+
+/// Tokenizes `expression` like [`tokenize`] with its own separators instead of the
+/// global ones.
+pub fn tokenize_with_config(expression: &str, config: &ParseConfig) -> TokenStream {
+    let expression = config.normalize(expression);
+    let expression = expression.as_ref();
+    let registry = operators();
+    let mut tokenizer = Tokenizer {
+        source: expression,
+        registry: &registry,
+        tokens: Vec::new(),
+        brackets: Vec::new(),
+        open: Vec::new(),
+    };
+
+    // `loose_statements` matches any input, so the grammar itself never fails
+    let pairs = CalculatorParser::parse(Rule::loose_statements, expression).unwrap();
+    tokenizer.walk_inner(pairs, 0, expression.len());
+    for (_, index) in std::mem::take(&mut tokenizer.open) {
+        tokenizer.tokens[index].kind = TokenKind::Error;
+    }
+
+    TokenStream {
+        tokens: tokenizer.tokens,
+        brackets: tokenizer.brackets,
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::Result;
+use pest::iterators::Pair;
+use pest::Parser;
+
+use crate::error::ParserError;
+
+use super::config::parse_config;
+use super::operators::operators;
+use super::parser::{
+    build_expr_tolerant, build_function, build_piecewise, differentiate_call, push_items,
+    CalculatorParser, Case, Item, Rule, TermParser,
+};
+use super::{resolve_name, Expr, Name, OperatorRegistry, ParseConfig, Span};
+
+/// Result of [`parse_tolerant`]: the best expression tree that could be built and
+/// every problem found on the way.
+#[derive(Debug)]
+pub struct PartialParse {
+    /// Parts that couldn't be parsed are replaced by [`Expr::Error`].
+    pub expr: Expr,
+    pub diagnostics: Vec<ParserError>,
+}
+
+/// Collects the diagnostics while walking the `loose_*` rules of the grammar.
+struct TolerantParser<'a> {
+    registry: &'a OperatorRegistry,
+    diagnostics: Vec<ParserError>,
+}
+
+impl TolerantParser<'_> {
+    /// Records the error of a failed step, keeping its span when it has one.
+    fn record<T>(&mut self, result: Result<T>, span: Span) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                match err.downcast::<ParserError>() {
+                    Ok(err) => self.diagnostics.push(err),
+                    Err(err) => self
+                        .diagnostics
+                        .push(ParserError::Syntax(err.to_string(), span)),
+                }
+                None
+            }
+        }
+    }
+
+    /// Pushes the items of a `loose_expr`. Errors are recorded rather than returned.
+    fn push_items(&mut self, items: &mut Vec<Item>, expr: Pair<Rule>) {
+        push_items(self, items, expr).unwrap();
+    }
+
+    fn parse_expr(&mut self, expr: Pair<Rule>) -> Expr {
+        let end = expr.as_span().end();
+        let mut items = Vec::new();
+        self.push_items(&mut items, expr);
+        build_expr_tolerant(items, self.registry, end, &mut self.diagnostics)
+    }
+
+    /// Parses the `loose_argument`s of a call or list. Empty ones are returned as
+    /// `None` so the caller can decide what they mean.
+    fn parse_arguments(&mut self, pairs: Vec<Pair<Rule>>) -> Vec<(Option<Expr>, Span)> {
+        pairs
+            .into_iter()
+            .map(|argument| {
+                let span = Span::from(argument.as_span());
+                let expr = argument
+                    .into_inner()
+                    .next()
+                    .map(|expr| self.parse_expr(expr));
+                (expr, span)
+            })
+            .collect()
+    }
+
+    /// Splits the pairs of a bracketed rule into its contents and whether the closing
+    /// bracket was found.
+    fn split_closing<'i>(pair: Pair<'i, Rule>, closing: Rule) -> (Vec<Pair<'i, Rule>>, bool) {
+        let mut pairs: Vec<Pair<Rule>> = pair.into_inner().collect();
+        let closed = pairs.last().is_some_and(|pair| pair.as_rule() == closing);
+        if closed {
+            pairs.pop();
+        }
+        (pairs, closed)
+    }
+
+    fn open_span(pair: &Pair<Rule>, symbol: char) -> Span {
+        let start = pair.as_str().find(symbol).unwrap_or(0) + pair.as_span().start();
+        Span::new(start, start + 1)
+    }
+
+    fn parse_function(&mut self, function: Pair<Rule>) -> Expr {
+        let span = Span::from(function.as_span());
+        let open = Self::open_span(&function, '(');
+        let (mut pairs, closed) = Self::split_closing(function, Rule::close_paren);
+        let name = pairs.remove(0);
+        let name = (name.as_str().to_string(), Span::from(name.as_span()));
+        let mut primes = 0;
+        if pairs
+            .first()
+            .is_some_and(|pair| pair.as_rule() == Rule::primes)
+        {
+            primes = pairs.remove(0).as_str().len() as u32;
+        }
+
+        if name.0 == "piecewise" {
+            let cases = pairs.into_iter().map(|arg| self.parse_case(arg)).collect();
+            let expr = build_piecewise(cases, span)
+                .and_then(|expr| differentiate_call(expr, (&name.0, name.1), primes));
+            let expr = self.record(expr, span);
+            if !closed {
+                self.diagnostics.push(ParserError::UnclosedParen(open));
+            }
+            return expr.unwrap_or(Expr::Error(span));
+        }
+
+        let mut arguments = self.parse_arguments(pairs);
+        // `f()` calls a user-defined function without parameters
+        if let [(None, _)] = arguments.as_slice() {
+            if matches!(resolve_name(&name.0), Name::Variable) {
+                arguments.clear();
+            }
+        }
+        let empty = arguments.iter().all(|(expr, _)| expr.is_none());
+        let mut args = Vec::new();
+        for (expr, arg_span) in arguments {
+            match expr {
+                Some(expr) => args.push(expr),
+                None if empty => {
+                    let err = ParserError::MissingArguments(name.0.clone(), span);
+                    self.diagnostics.push(err);
+                    args.push(Expr::Error(arg_span));
+                }
+                None => {
+                    self.diagnostics
+                        .push(ParserError::ExpectedOperand(arg_span));
+                    args.push(Expr::Error(arg_span));
+                }
+            }
+        }
+        if !closed {
+            self.diagnostics.push(ParserError::UnclosedParen(open));
+        }
+        let (function_name, name_span) = (name.0.clone(), name.1);
+        let expr = build_function(name, args, span)
+            .and_then(|expr| differentiate_call(expr, (&function_name, name_span), primes));
+        let expr = self.record(expr, span);
+        expr.unwrap_or(Expr::Error(span))
+    }
+
+    /// Parses the expressions in a pair of parentheses, which is a tuple when there
+    /// are several of them.
+    fn parse_parenthesized(&mut self, group: Pair<Rule>) -> Vec<Expr> {
+        let open = Self::open_span(&group, '(');
+        let (mut pairs, closed) = Self::split_closing(group, Rule::close_paren);
+        // A keyword operator written before the parentheses isn't part of the group
+        pairs.retain(|pair| pair.as_rule() != Rule::identifier);
+        let items = self
+            .parse_arguments(pairs)
+            .into_iter()
+            .map(|(expr, span)| {
+                expr.unwrap_or_else(|| {
+                    self.diagnostics.push(ParserError::ExpectedOperand(span));
+                    Expr::Error(span)
+                })
+            })
+            .collect();
+        if !closed {
+            self.diagnostics.push(ParserError::UnclosedParen(open));
+        }
+        items
+    }
+
+    /// Parses an argument of `piecewise`, where a tuple is a case.
+    fn parse_case(&mut self, argument: Pair<Rule>) -> Case {
+        let span = Span::from(argument.as_span());
+        let Some(expr) = argument.into_inner().next() else {
+            self.diagnostics.push(ParserError::ExpectedOperand(span));
+            return Case::Value(Expr::Error(span));
+        };
+        let mut terms = expr.clone().into_inner();
+        if let (Some(group), None) = (terms.next(), terms.next()) {
+            let arguments = group.clone().into_inner();
+            let count = arguments
+                .filter(|pair| pair.as_rule() == Rule::loose_argument)
+                .count();
+            if group.as_rule() == Rule::loose_group && count > 1 {
+                let span = Span::from(group.as_span());
+                return Case::Tuple(self.parse_parenthesized(group), span);
+            }
+        }
+        Case::Value(self.parse_expr(expr))
+    }
+
+    /// Parses the items of a list or set, given its opening bracket and the rule of
+    /// its closing one.
+    fn parse_items(&mut self, list: Pair<Rule>, (bracket, closing): (char, Rule)) -> Vec<Expr> {
+        let open = Self::open_span(&list, bracket);
+        let (pairs, closed) = Self::split_closing(list, closing);
+        let arguments = self.parse_arguments(pairs);
+        let mut items = Vec::new();
+        // `[]` is an empty list rather than a missing item, as `{}` is an empty set
+        if !matches!(arguments.as_slice(), [(None, _)]) {
+            for (expr, span) in arguments {
+                items.push(expr.unwrap_or_else(|| {
+                    self.diagnostics.push(ParserError::ExpectedOperand(span));
+                    Expr::Error(span)
+                }));
+            }
+        }
+        if !closed {
+            self.diagnostics.push(match bracket {
+                '{' => ParserError::UnclosedBrace(open),
+                _ => ParserError::UnclosedBracket(open),
+            });
+        }
+        items
+    }
+}
+
+impl TermParser for TolerantParser<'_> {
+    fn registry(&self) -> &OperatorRegistry {
+        self.registry
+    }
+
+    fn recover<T>(&mut self, result: Result<T>, span: Span, fallback: T) -> Result<T> {
+        Ok(self.record(result, span).unwrap_or(fallback))
+    }
+
+    fn function(&mut self, function: Pair<Rule>) -> Result<Expr> {
+        Ok(self.parse_function(function))
+    }
+
+    fn parenthesized(&mut self, group: Pair<Rule>) -> Result<Vec<Expr>> {
+        Ok(self.parse_parenthesized(group))
+    }
+
+    fn bracketed(&mut self, pair: Pair<Rule>) -> Result<Vec<Expr>> {
+        Ok(match pair.as_rule() {
+            Rule::loose_set => self.parse_items(pair, ('{', Rule::close_brace)),
+            Rule::loose_list => self.parse_items(pair, ('[', Rule::close_bracket)),
+            _ => {
+                let pairs = pair.into_inner().collect();
+                let ends = self.parse_arguments(pairs).into_iter();
+                ends.map(|(expr, span)| {
+                    expr.unwrap_or_else(|| {
+                        self.diagnostics.push(ParserError::ExpectedOperand(span));
+                        Expr::Error(span)
+                    })
+                })
+                .collect()
+            }
+        })
+    }
+}
+
+/// Parses `expression` without stopping at the first error, for editors that need a
+/// tree of input that is still being typed. Missing or malformed parts become
+/// [`Expr::Error`] nodes and every problem is listed in the diagnostics. Input that
+/// [`parse`](super::parse) accepts gives the same tree and no diagnostics.
+pub fn parse_tolerant(expression: &str) -> PartialParse {
+    parse_tolerant_with_config(expression, &parse_config())
+}
+
+/// Parses `expression` like [`parse_tolerant`] with its own separators instead of the
+/// global ones.
+pub fn parse_tolerant_with_config(expression: &str, config: &ParseConfig) -> PartialParse {
+    let registry = operators();
+    let mut parser = TolerantParser {
+        registry: &registry,
+        diagnostics: Vec::new(),
+    };
+
+    let expression = config.normalize(expression);
+    // `loose` matches any input, so the grammar itself never fails
+    let pairs = CalculatorParser::parse(Rule::loose, &expression).unwrap();
+    let mut items = Vec::new();
+    for pair in pairs {
+        let span = Span::from(pair.as_span());
+        match pair.as_rule() {
+            Rule::loose_expr => parser.push_items(&mut items, pair),
+            Rule::stray => parser.diagnostics.push(ParserError::UnexpectedToken(
+                pair.as_str().to_string(),
+                span,
+            )),
+            _ => {}
+        }
+    }
+
+    let expr = build_expr_tolerant(items, &registry, expression.len(), &mut parser.diagnostics);
+    // Nested parts are finished first, but editors expect the problems in order
+    let mut diagnostics = parser.diagnostics;
+    diagnostics.sort_by_key(|err| err.span().start);
+    PartialParse { expr, diagnostics }
+}
@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod test {
-    use crate::numeric_evaluator::evaluate;
+    use crate::numeric_evaluator::{
+        environment, evaluate, evaluate_value, evaluate_with, evaluate_with_config, Complex, Environment,
+        Matrix, Value,
+    };
+    use crate::parser::ParseConfig;
 
     #[test]
     fn can_eval_plus() {
@@ -88,6 +92,7 @@ mod test {
         assert_eq!(16.0, evaluate("pow(4, 2)").unwrap());
         assert_eq!(2.0, evaluate("min(4, 2)").unwrap());
         assert_eq!(4.0, evaluate("max(4, 2)").unwrap());
+        assert_eq!(4.0, evaluate("abs(-4)").unwrap());
 
         assert_eq!(6.0, evaluate("max(1, 2) + 4").unwrap());
         assert_eq!(8.0, evaluate("4 + min(5, 4)").unwrap());
@@ -104,4 +109,1086 @@ mod test {
         assert_eq!(1.618033988749895, evaluate("phi").unwrap());
         assert_eq!(2.718281828459045, evaluate("e").unwrap());
     }
+
+    #[test]
+    fn can_eval_constant_coefficients() {
+        assert_eq!(6.283185307179586, evaluate("2pi").unwrap());
+    }
+
+    #[test]
+    fn fails_on_unknown_variables() {
+        let err = evaluate("x + 1").unwrap_err();
+        assert_eq!("Unknown variable 'x'", err.to_string());
+    }
+
+    #[test]
+    fn can_eval_implicit_multiplication() {
+        assert_eq!(14.0, evaluate("2(3+4)").unwrap());
+        assert_eq!(1.5, evaluate("3sin(30)").unwrap());
+        assert_eq!(-3.0, evaluate("(1+1)(1-2)(3)/2").unwrap());
+        assert_eq!(0.25, evaluate("1/2(2)").unwrap());
+    }
+
+    #[test]
+    fn can_eval_number_literals() {
+        assert_eq!(6.022e23, evaluate("6.022e23").unwrap());
+        assert_eq!(256.0, evaluate("0xFF + 0b1").unwrap());
+        assert_eq!(1000001.0, evaluate("1_000_000 + 1").unwrap());
+        assert_eq!(1.5, evaluate(".5 + 1").unwrap());
+    }
+
+    #[test]
+    fn can_eval_repeating_decimals() {
+        assert_eq!(1.0, evaluate("0.(3) * 3").unwrap());
+        assert_eq!(0.166666666666667, evaluate("0.1(6)").unwrap());
+        assert_eq!(1.0, evaluate("0.(9)").unwrap());
+    }
+
+    #[test]
+    fn can_eval_factorials() {
+        assert_eq!(120.0, evaluate("5!").unwrap());
+        assert_eq!(1.0, evaluate("0!").unwrap());
+        assert_eq!(-6.0, evaluate("-3!").unwrap());
+        assert!((evaluate("0.5!").unwrap() - 0.886226925452758).abs() < 1e-14);
+        assert!((evaluate("1.5!").unwrap() - 1.329340388179137).abs() < 1e-14);
+        assert!(evaluate("(-1)!").unwrap().is_nan());
+    }
+
+    #[test]
+    fn can_eval_double_factorials() {
+        assert_eq!(15.0, evaluate("5!!").unwrap());
+        assert_eq!(48.0, evaluate("6!!").unwrap());
+        assert_eq!(1.0, evaluate("0!!").unwrap());
+        assert_eq!(f64::INFINITY, evaluate("1e9!!").unwrap());
+        assert_eq!(f64::INFINITY, evaluate("1e17!!").unwrap());
+    }
+
+    #[test]
+    fn can_eval_percentages() {
+        assert_eq!(0.1, evaluate("10%").unwrap());
+        assert_eq!(55.0, evaluate("50 + 10%").unwrap());
+        assert_eq!(45.0, evaluate("50 - 10%").unwrap());
+        assert_eq!(20.0, evaluate("200 * 10%").unwrap());
+        assert_eq!(1.0, evaluate("10 % 3").unwrap());
+    }
+
+    #[test]
+    fn can_eval_absolute_value_bars() {
+        assert_eq!(2.0, evaluate("|1 - 3|").unwrap());
+        assert_eq!(2.0, evaluate("||-3| - 5|").unwrap());
+        assert_eq!(6.0, evaluate("2|-3|").unwrap());
+        assert_eq!(3.0, evaluate("||-3||").unwrap());
+    }
+
+    #[test]
+    fn can_eval_unicode_symbols() {
+        assert_eq!(6.0, evaluate("2×3").unwrap());
+        assert_eq!(1.5, evaluate("2·3÷4").unwrap());
+        assert_eq!(-2.0, evaluate("−5 − −3").unwrap());
+        assert_eq!(3.0, evaluate("√9").unwrap());
+        assert_eq!(-2.0, evaluate("∛−8").unwrap());
+        assert_eq!(3.141592653589793, evaluate("π").unwrap());
+        assert_eq!(evaluate("2*pi").unwrap(), evaluate("2π").unwrap());
+        assert_eq!(evaluate("tau").unwrap(), evaluate("τ").unwrap());
+        assert_eq!(9.0, evaluate("3²").unwrap());
+        assert_eq!(0.5, evaluate("2⁻¹").unwrap());
+        assert!(evaluate("1 ≤ 2").is_err());
+    }
+
+    #[test]
+    fn can_eval_relations() {
+        assert_eq!(Value::Bool(true), evaluate_value("1 < 2").unwrap());
+        assert_eq!(Value::Bool(false), evaluate_value("2 <= 1").unwrap());
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_value("0.1 + 0.2 = 0.3").unwrap()
+        );
+        assert_eq!(Value::Bool(false), evaluate_value("1e300 = 2e300").unwrap());
+        assert_eq!(Value::Bool(true), evaluate_value("1e300 < 2e300").unwrap());
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_value("1e300 * 3 = 3e300").unwrap()
+        );
+        assert_eq!(Value::Bool(false), evaluate_value("1e308 = inf").unwrap());
+        assert_eq!(Value::Bool(false), evaluate_value("1e-20 < 0").unwrap());
+        assert_eq!(Value::Bool(true), evaluate_value("3! != 5").unwrap());
+        assert_eq!(Value::Bool(true), evaluate_value("0 < 3 <= 3").unwrap());
+        assert_eq!(Value::Bool(false), evaluate_value("0 < 5 < 3").unwrap());
+        assert_eq!(Value::Bool(true), evaluate_value("2 ≥ 1 > -1").unwrap());
+        assert_eq!(Value::Number(3.0), evaluate_value("1 + 2").unwrap());
+        assert_eq!(
+            "Expected a number but found a boolean",
+            evaluate("1 < 2").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn can_compare_booleans() {
+        let eval = |expression| evaluate_value(expression).unwrap();
+        assert_eq!(Value::Bool(false), eval("true = false"));
+        assert_eq!(Value::Bool(true), eval("true != false"));
+        assert_eq!(Value::Bool(true), eval("not true = false"));
+        assert_eq!(Value::Bool(true), eval("(1 < 2) = true"));
+        assert_eq!(Value::Bool(true), eval("(1 < 2) = (3 > 2) != false"));
+        assert_eq!(
+            "Expected a number but found a boolean",
+            evaluate_value("true < false").unwrap_err().to_string()
+        );
+        assert!(evaluate_value("true = 1").is_err());
+    }
+
+    #[test]
+    fn compares_small_magnitudes_relatively() {
+        let eval = |expression| evaluate_value(expression).unwrap();
+        assert_eq!(Value::Bool(true), eval("6.626e-34 < 1.6e-19"));
+        assert_eq!(Value::Bool(true), eval("1e-20 < 2e-20"));
+        assert_eq!(Value::Bool(false), eval("1e-16 = 2e-16"));
+        assert_eq!(Value::Bool(true), eval("sin(180) = 0"));
+        assert_eq!(Value::Bool(true), eval("cos(90) = 0"));
+        assert_eq!(Value::Bool(true), eval("0.1 + 0.2 - 0.3 = 0"));
+        assert_eq!(Value::Bool(true), eval("0 in {sin(180)}"));
+        assert_eq!(Value::Number(1.0), eval("if(sin(180) = 0, 1, 2)"));
+        assert_eq!(Value::Bool(true), eval("6.626e-34 * 3 = 1.9878e-33"));
+        assert_eq!(Value::Bool(false), eval("1.6e-19 in (2e-19, 1)"));
+        assert_eq!(Value::Bool(true), eval("6.626e-34 in {6.626e-34, 1.6e-19}"));
+        assert_eq!(Value::Bool(false), eval("6.626e-34 in {1.6e-19}"));
+    }
+
+    #[test]
+    fn can_eval_logic() {
+        let eval = |expression| evaluate_value(expression).unwrap();
+        assert_eq!(Value::Bool(true), eval("true"));
+        assert_eq!(Value::Bool(false), eval("true and false"));
+        assert_eq!(Value::Bool(true), eval("false or 1 < 2"));
+        assert_eq!(Value::Bool(true), eval("true xor false"));
+        assert_eq!(Value::Bool(false), eval("true xor true"));
+        assert_eq!(Value::Bool(true), eval("false implies false"));
+        assert_eq!(Value::Bool(false), eval("true implies false"));
+        assert_eq!(Value::Bool(false), eval("not 1 < 2"));
+        assert_eq!(Value::Bool(true), eval("not (true and false) or false"));
+        assert_eq!(Value::Bool(true), eval("1 < 2 and 2 < 3 implies 1 < 3"));
+        assert_eq!(Value::Number(1.0), eval("if(0 < 1 and true, 1, 2)"));
+
+        // The right hand side is skipped once the left one decides the result
+        assert_eq!(Value::Bool(false), eval("false and 1"));
+        assert_eq!(Value::Bool(true), eval("true or 1"));
+        assert_eq!(Value::Bool(true), eval("false implies 1"));
+
+        let mut env = Environment::new();
+        evaluate_with(
+            "p := true; q := 1 > 2; nand(a, b) := not (a and b)",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_with("nand(p, q)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Bool(false),
+            evaluate_with("p and q", &mut env).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_logic() {
+        assert_eq!(
+            "Expected a condition but found a number",
+            evaluate_value("1 and true").unwrap_err().to_string()
+        );
+        assert!(evaluate_value("not 2").is_err());
+        assert!(evaluate_value("false or true + 1").is_err());
+        assert!(evaluate("true").is_err());
+        assert!(evaluate_value("true := 1").is_err());
+    }
+
+    #[test]
+    fn can_eval_sets() {
+        let eval = |expression| evaluate_value(expression).unwrap().to_string();
+        assert_eq!("[0, 1)", eval("[0, 1)"));
+        assert_eq!("{1, 2, 3}", eval("{3, 1, 2, 1}"));
+        assert_eq!("{}", eval("{}"));
+        assert_eq!("(-inf, -2) ∪ (2, inf)", eval("(-inf, inf) \\ [-2, 2]"));
+        assert_eq!("[0, 2]", eval("[0, 1] union (1, 2]"));
+        assert_eq!("[0, 1) ∪ {2, 3}", eval("[0, 1) ∪ {2, 3}"));
+        assert_eq!("(1, 2)", eval("(0, 2) intersect (1, 3)"));
+        assert_eq!("{}", eval("[0, 1) ∩ [1, 2]"));
+        assert_eq!("[0, 1) ∪ (1, 2]", eval("[0, 2] \\ {1}"));
+        assert_eq!("{1, 2}", eval("{1, 2, 3} intersect [1, 2]"));
+        assert_eq!("{3}", eval("[1, 2, 3] \\ [1, 2]"));
+        assert_eq!("true", eval("-5 in (-inf, -2) ∪ (2, inf)"));
+        assert_eq!("false", eval("2 in (-inf, -2) ∪ (2, inf)"));
+        assert_eq!("true", eval("1 in [0, 1]"));
+        assert_eq!("false", eval("1 in [0, 1)"));
+        assert_eq!("true", eval("0.1 + 0.2 in {0.3}"));
+        assert_eq!("{0.3}", eval("{0.3, 0.1 + 0.2}"));
+        assert_eq!("{}", eval("[0.3, 0.1 + 0.2)"));
+        assert_eq!("(0, 0.3)", eval("(0, 0.3] \\ [0.1 + 0.2, 1)"));
+        assert_eq!("false", eval("1e300 in {2e300}"));
+        assert_eq!("true", eval("3e300 in [1e300, 1e300 * 3]"));
+        assert_eq!("true", eval("2e300 in {1e300, 2e300} \\ {1e300}"));
+        assert_eq!("true", eval("3 in {1, 2, 3} and not 4 ∈ {1}"));
+    }
+
+    #[test]
+    fn rejects_invalid_sets() {
+        assert_eq!(
+            "Expected a set but found a number",
+            evaluate_value("1 union [0, 1]").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "The lower end of an interval can't be above its upper end",
+            evaluate_value("[2, 1)").unwrap_err().to_string()
+        );
+        assert!(evaluate_value("[0, 1) in [0, 2]").is_err());
+        assert!(evaluate_value("{[1, 2]}").is_err());
+        assert!(evaluate_value("[0, 1) + 1").is_err());
+        assert!(evaluate("{1}").is_err());
+    }
+
+    #[test]
+    fn can_eval_multivariate_monomials() {
+        let mut env = Environment::new();
+        evaluate_with("X := 4; Y := 3", &mut env).unwrap();
+        let mut eval = |expression| evaluate_with(expression, &mut env).unwrap();
+        assert_eq!(Value::Number(144.0), eval("3X^2Y"));
+        assert_eq!(Value::Number(12.0), eval("XY"));
+        assert_eq!(Value::Number(2.0), eval("X^(1/2)"));
+        assert_eq!(Value::Number(0.25), eval("X^-1"));
+        assert_eq!(Value::Number(-2.0), eval("X := -8; X^(1/3)"));
+        assert_eq!(Value::Number(4.0), eval("X^(2/3)"));
+        assert_eq!(
+            Value::list(vec![Value::Number(3.0), Value::Number(8.0)]),
+            eval("X := [1, 2]; Y := [3, 4]; XY")
+        );
+
+        // Capital names would read back as products
+        assert!(evaluate_with("AB := 3", &mut env).is_err());
+        assert!(evaluate_with("f(AB) := AB", &mut env).is_err());
+        assert!(evaluate_with("XZ", &mut env).is_err());
+    }
+
+    #[test]
+    fn can_eval_statements() {
+        let mut env = Environment::new();
+        assert_eq!(
+            Value::Number(10.0),
+            evaluate_with("a := 3; b := a^2; b + 1", &mut env).unwrap()
+        );
+        assert_eq!(Some(&Value::Number(9.0)), env.get("b"));
+        assert_eq!(Value::Number(18.0), evaluate_with("2b", &mut env).unwrap());
+        assert_eq!(
+            Value::Number(4.0),
+            evaluate_with("a := a + 1", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_with("t := a > 3\nt", &mut env).unwrap()
+        );
+        assert!(evaluate_with("t + 1", &mut env).is_err());
+        assert!(evaluate_with("c", &mut env).is_err());
+    }
+
+    #[test]
+    fn keeps_variables_between_evaluations() {
+        assert_eq!(5.0, evaluate("shared_width := 5").unwrap());
+        assert_eq!(
+            20.0,
+            evaluate("shared_height := 4; shared_width * shared_height").unwrap()
+        );
+        assert_eq!(
+            Some(&Value::Number(4.0)),
+            environment().get("shared_height")
+        );
+        assert!(evaluate("shared_width := 1 +").is_err());
+        assert_eq!(5.0, evaluate("shared_width").unwrap());
+    }
+
+    #[test]
+    fn keeps_concurrent_assignments() {
+        let threads: Vec<_> = (0..8)
+            .map(|i| std::thread::spawn(move || evaluate(&format!("concurrent_{i} := {i}"))))
+            .collect();
+        for thread in threads {
+            thread.join().unwrap().unwrap();
+        }
+        for i in 0..8 {
+            assert_eq!(
+                i as f64,
+                evaluate(&format!("concurrent_{i}")).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_variables_in_own_environment() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env);
+        assert_eq!(Value::Number(5.0), eval("width := 5").unwrap());
+        assert_eq!(
+            Value::Number(20.0),
+            eval("height := 4; width * height").unwrap()
+        );
+        assert!(eval("width := 1 +").is_err());
+        assert_eq!(Value::Number(5.0), eval("width").unwrap());
+        assert_eq!(Some(&Value::Number(4.0)), env.get("height"));
+    }
+
+    #[test]
+    fn can_eval_with_separators() {
+        let german = ParseConfig::new(',', ';', Some('.')).unwrap();
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with_config(expression, &mut env, &german).unwrap();
+        assert_eq!(Value::Number(1000.5), eval("max(1.000,5; 2)"));
+        assert_eq!(Value::Number(5.0), eval("a := 2,5; a * 2"));
+    }
+
+    #[test]
+    fn can_eval_real_exponents_of_variables() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env).unwrap();
+        assert_eq!(
+            Value::Bool(true),
+            eval("X := 2; X^1.2345678 = 2^1.2345678")
+        );
+        assert_eq!(Value::Number(3.0), eval("Y := 1; 3Y^1e20"));
+    }
+
+    #[test]
+    fn can_divide_variables_named_like_derivatives() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env).unwrap();
+        assert_eq!(Value::Number(5.0), eval("d := 10; density := 2; d/density"));
+        assert_eq!(Value::Number(4.0), eval("dt := 2.5; d/dt"));
+        assert_eq!(Value::Number(4.0), eval("d / dt"));
+        assert_eq!(Value::Number(5.0), eval("d/dt + 1"));
+    }
+
+    #[test]
+    fn keeps_assigned_values_unrounded() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env).unwrap();
+        assert_eq!(Value::Number(0.0), eval("a := 1e-20"));
+        assert_eq!(Value::Number(1.0), eval("a * 1e20"));
+        assert_eq!(Value::Number(1e300), eval("b := 1e300; b"));
+        assert_eq!(
+            Value::Bool(true),
+            eval("c := 0.1 + 0.2; (c - 0.3) * 1e16 > 0")
+        );
+    }
+
+    #[test]
+    fn can_eval_functions_without_parameters() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env);
+        assert_eq!(Value::Number(3.0), eval("answer() := 3; answer()").unwrap());
+        assert_eq!(Value::Number(7.0), eval("2answer() + 1").unwrap());
+        assert_eq!(
+            Value::Number(6.0),
+            eval("n := 2; scaled() := n * answer(); scaled()").unwrap()
+        );
+        assert!(eval("answer(1)").is_err());
+    }
+
+    #[test]
+    fn can_eval_user_functions() {
+        let mut env = Environment::new();
+        evaluate_with("f(X) := X^2 + 1\ng(a, b) := sqrt(a^2+b^2)", &mut env).unwrap();
+        assert_eq!(
+            Value::Number(10.0),
+            evaluate_with("f(3)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(2.0),
+            evaluate_with("g(f(1), 0)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(5.0),
+            evaluate_with("g(3, 4)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(11.0),
+            evaluate_with("f(-3) + 1", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_with("h(X) := X > 2; h(3)", &mut env).unwrap()
+        );
+        assert!(evaluate_with("f(1, 2)", &mut env).is_err());
+        assert!(evaluate_with("h(3) + 1", &mut env).is_err());
+    }
+
+    #[test]
+    fn calls_functions_unrounded() {
+        let mut env = Environment::new();
+        let mut eval = |expression| evaluate_with(expression, &mut env).unwrap();
+        assert_eq!(Value::Number(1.0), eval("f(X) := X; f(1e-20) * 1e20"));
+        assert_eq!(Value::Number(1e300), eval("f(1e300)"));
+        assert_eq!(Value::Number(1.0), eval("g(X) := X / 1e20; g(1) * 1e20"));
+        assert_eq!(Value::Number(1.0), eval("map(g, [1])[1] * 1e20"));
+    }
+
+    #[test]
+    fn scopes_function_parameters() {
+        let mut env = Environment::new();
+        evaluate_with("X := 10; a := 1; f(X) := X + a", &mut env).unwrap();
+        assert_eq!(Value::Number(3.0), evaluate_with("f(2)", &mut env).unwrap());
+        assert_eq!(Value::Number(10.0), evaluate_with("X", &mut env).unwrap());
+        assert_eq!(
+            Value::Number(5.0),
+            evaluate_with("a := 3; f(2)", &mut env).unwrap()
+        );
+        evaluate_with("g(a) := f(a) * X", &mut env).unwrap();
+        assert_eq!(
+            Value::Number(50.0),
+            evaluate_with("g(2)", &mut env).unwrap()
+        );
+        assert!(evaluate_with("k(Y) := Y + Z; k(1)", &mut env).is_err());
+    }
+
+    #[test]
+    fn limits_recursion_depth() {
+        let mut env = Environment::new();
+        evaluate_with("f(X) := f(X + 1) + 1", &mut env).unwrap();
+        assert_eq!(
+            "Function 'f' exceeded the maximum recursion depth",
+            evaluate_with("f(1)", &mut env).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn can_eval_piecewise() {
+        assert_eq!(2.0, evaluate("if(1 < 2, 2, 3)").unwrap());
+        assert_eq!(3.0, evaluate("if(1 > 2, 2, 3)").unwrap());
+        assert_eq!(
+            30.0,
+            evaluate("piecewise((10, 5 < 0), (30, 5 < 10), 50)").unwrap()
+        );
+        assert_eq!(
+            50.0,
+            evaluate("piecewise((10, 15 < 0), (30, 15 < 10), 50)").unwrap()
+        );
+        assert_eq!(1.0, evaluate("if(0 < 1, 1, 1/0 + [1])").unwrap());
+
+        let mut env = Environment::new();
+        evaluate_with("fact(n) := if(n <= 1, 1, n * fact(n - 1))", &mut env).unwrap();
+        assert_eq!(
+            Value::Number(120.0),
+            evaluate_with("fact(5)", &mut env).unwrap()
+        );
+        evaluate_with(
+            "tax(i) := piecewise((0, i <= 10000), (0.2 * (i - 10000), i <= 50000), 8000 + 0.4 * (i - 50000))",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(
+            Value::Number(4000.0),
+            evaluate_with("tax(30000)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(12000.0),
+            evaluate_with("tax(60000)", &mut env).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_piecewise() {
+        assert_eq!(
+            "None of the piecewise conditions hold",
+            evaluate("piecewise((1, 2 < 1))").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected a condition but found a number",
+            evaluate("if(1, 2, 3)").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Function 'if' takes 3 argument(s) but 2 were given",
+            evaluate("if(1 < 2, 3)").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn can_eval_series() {
+        assert_eq!(55.0, evaluate("sum(k, 1, 10, k)").unwrap());
+        assert_eq!(120.0, evaluate("prod(k, 1, 5, k)").unwrap());
+        assert_eq!(0.0, evaluate("sum(k, 5, 1, k)").unwrap());
+        assert_eq!(1.0, evaluate("prod(k, 5, 1, k)").unwrap());
+        assert_eq!(
+            6.0,
+            evaluate("sum(k, -1, 2, k^2) + sum(k, 3, 3, 0)").unwrap()
+        );
+        assert_eq!(6.0, evaluate("sum(i, 1, 3, sum(j, 1, i, 1))").unwrap());
+        assert!(evaluate("sum(k, 1, 2000000, 1)").is_err());
+        assert_eq!(6.0, evaluate("sum([1, 2, 3])").unwrap());
+        assert_eq!("0", evaluate_value("sum([])").unwrap().to_string());
+        assert_eq!("0", evaluate_value("sum(k, 5, 1, k)").unwrap().to_string());
+        assert_eq!(6.0, evaluate("prod([1, 2, 3])").unwrap());
+
+        let mut env = Environment::new();
+        evaluate_with("k := 100; n := 4; f(X) := sum(k, 1, X, k)", &mut env).unwrap();
+        assert_eq!(
+            Value::Number(10.0),
+            evaluate_with("sum(k, 1, n, k)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(10.0),
+            evaluate_with("f(n)", &mut env).unwrap()
+        );
+        assert_eq!(Value::Number(100.0), evaluate_with("k", &mut env).unwrap());
+    }
+
+    #[test]
+    fn can_eval_infinite_series() {
+        // Every printed digit is right and there are at least eight decimals of them
+        let close = |expected: f64, expression: &str| {
+            let value = evaluate(expression).unwrap();
+            let decimals = value
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, decimals)| decimals.len());
+            let error = (value - expected).abs();
+            assert!(
+                error < 10f64.powi(-(decimals as i32)),
+                "{expression} = {value}"
+            );
+            assert!(error < 1e-8, "{expression} = {value}");
+        };
+        close(1.0, "sum(k, 1, inf, 1/2^k)");
+        close(std::f64::consts::PI.powi(2) / 6.0, "sum(k, 1, inf, 1/k^2)");
+        assert_eq!(1.64493407, evaluate("sum(k, 1, inf, 1/k^2)").unwrap());
+        close(std::f64::consts::LN_2, "sum(k, 1, inf, (-1)^(k+1)/k)");
+        close(std::f64::consts::E, "sum(k, 0, inf, 1/k!)");
+        close(std::f64::consts::PI / 4.0, "sum(k, 0, inf, (-1)^k/(2k+1))");
+        close(0.5, "prod(k, 2, inf, 1 - 1/k^2)");
+        close(1.0, "sum(k, -inf, -1, 1/2^(-k))");
+        close(
+            std::f64::consts::PI.powi(2) / 3.0,
+            "sum(k, -inf, inf, if(k = 0, 0, 1/k^2))",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_series() {
+        assert_eq!(
+            "The series doesn't converge",
+            evaluate("sum(k, 1, inf, 1)").unwrap_err().to_string()
+        );
+        assert!(evaluate("sum(k, 1, inf, 1/k)").is_err());
+        assert!(evaluate("sum(k, 0, inf, (-1)^k)").is_err());
+        assert!(evaluate("prod(k, 1, inf, k)").is_err());
+        assert!(evaluate("sum(k, 1.5, 3, k)").is_err());
+        assert!(evaluate("sum(k, inf, 3, k)").is_err());
+        assert!(evaluate("sum(k, 1, 1e7, k)").is_err());
+        assert!(evaluate("sum(k, 1e17, 1e17+100, k)").is_err());
+        assert!(evaluate("sum(2, 1, 3, 4)").is_err());
+    }
+
+    fn list(items: &[f64]) -> Value {
+        Value::List(items.iter().map(|item| Value::Number(*item)).collect())
+    }
+
+    #[test]
+    fn can_eval_lists() {
+        assert_eq!(
+            list(&[1.0, 5.0, 4.0]),
+            evaluate_value("[1, 2 + 3, 2^2]").unwrap()
+        );
+        assert_eq!(list(&[]), evaluate_value("[]").unwrap());
+        assert_eq!(list(&[2.0, 4.0]), evaluate_value("[1, 2] * 2").unwrap());
+        assert_eq!(
+            list(&[4.0, 6.0]),
+            evaluate_value("[1, 2] + [3, 4]").unwrap()
+        );
+        assert_eq!(list(&[1.0, 0.5]), evaluate_value("1 / [1, 2]").unwrap());
+        assert_eq!(
+            list(&[1.0, 12.0]),
+            evaluate_value("-[1, -2]^2 + [0, 8]").unwrap()
+        );
+        assert_eq!(list(&[1.0, 2.0]), evaluate_value("sqrt([1, 4])").unwrap());
+        assert_eq!(list(&[0.3]), evaluate_value("[0.1] + 0.2").unwrap());
+        assert_eq!(
+            "Lists of length 2 and 3 can't be combined element-wise",
+            evaluate_value("[1, 2] + [1, 2, 3]")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(evaluate("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn can_index_lists() {
+        let mut env = Environment::new();
+        evaluate_with("v := [10, 20, 30]", &mut env).unwrap();
+        assert_eq!(
+            Value::Number(20.0),
+            evaluate_with("v[2]", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(31.0),
+            evaluate_with("v[1+2] + 1", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(-10.0),
+            evaluate_with("-v[1]", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(4.0),
+            evaluate_with("[[1, 2], [3, 4]][2][2]", &mut env).unwrap()
+        );
+        assert_eq!(
+            "Index 4 is out of range for a list of length 3",
+            evaluate_with("v[4]", &mut env).unwrap_err().to_string()
+        );
+        assert!(evaluate_with("v[0]", &mut env).is_err());
+        assert!(evaluate_with("v[1.5]", &mut env).is_err());
+        assert!(evaluate_with("v[1][1]", &mut env).is_err());
+    }
+
+    #[test]
+    fn can_eval_ranges() {
+        assert_eq!(list(&[1.0, 2.0, 3.0, 4.0]), evaluate_value("1..4").unwrap());
+        assert_eq!(
+            list(&[2.0, 4.0, 6.0]),
+            evaluate_value("2 * (1..3)").unwrap()
+        );
+        assert_eq!(list(&[0.5, 1.5]), evaluate_value("0.5..2").unwrap());
+        assert_eq!(list(&[]), evaluate_value("3..1").unwrap());
+        assert_eq!(list(&[1.0, 2.0]), evaluate_value("range(1, 2)").unwrap());
+        assert!(evaluate_value("1..inf").is_err());
+        assert!(evaluate_value("0..1e7").is_err());
+        assert!(evaluate_value("1e17..1e17+100").is_err());
+    }
+
+    #[test]
+    fn can_aggregate_lists() {
+        assert_eq!(55.0, evaluate("sum(1..10)").unwrap());
+        assert_eq!(2.5, evaluate("mean([1, 2, 3, 4])").unwrap());
+        assert_eq!(3.0, evaluate("len(1..3)").unwrap());
+        assert_eq!(-1.0, evaluate("min([3, -1, 2])").unwrap());
+        assert_eq!(3.0, evaluate("max([3, -1, 2])").unwrap());
+        assert_eq!(5.0, evaluate("norm([3, 4])").unwrap());
+        assert_eq!(14.0, evaluate("sum([1, 2, 3]^2)").unwrap());
+        assert_eq!(3.0, evaluate("max(1, 3)").unwrap());
+        assert_eq!(
+            "Expected a list but found a number",
+            evaluate("sum(3)").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn can_eval_lambdas() {
+        assert_eq!(
+            list(&[1.0, 4.0, 9.0]),
+            evaluate_value("map(X -> X^2, [1, 2, 3])").unwrap()
+        );
+        assert_eq!(
+            list(&[2.0, 4.0]),
+            evaluate_value("filter(X -> X%2 = 0, 1..5)").unwrap()
+        );
+        assert_eq!(
+            10.0,
+            evaluate("reduce((a, b) -> a + b, [1, 2, 3, 4])").unwrap()
+        );
+        assert_eq!(24.0, evaluate("reduce((a, b) -> a*b, 1..4, 1)").unwrap());
+        assert_eq!(5.0, evaluate("reduce((a, b) -> a + b, [], 5)").unwrap());
+        assert_eq!(7.0, evaluate("apply((a, b) -> a + 2b, [3, 2])").unwrap());
+        assert_eq!(
+            list(&[3.0, 7.0]),
+            evaluate_value("map(row -> sum(row), [[1, 2], [3, 4]])").unwrap()
+        );
+        assert_eq!(
+            list(&[3.0, 6.0]),
+            evaluate_value("apply(X -> map(Y -> X*Y, [1, 2]), [3])").unwrap()
+        );
+
+        let mut env = Environment::new();
+        evaluate_with(
+            "square := X -> X^2; add(n) := X -> X + n; inc := add(1)",
+            &mut env,
+        )
+        .unwrap();
+        assert_eq!(
+            Value::Number(16.0),
+            evaluate_with("square(4)", &mut env).unwrap()
+        );
+        assert_eq!(
+            Value::Number(3.0),
+            evaluate_with("inc(2)", &mut env).unwrap()
+        );
+        assert_eq!(
+            list(&[2.0, 3.0]),
+            evaluate_with("map(inc, [1, 2])", &mut env).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lambdas() {
+        assert_eq!(
+            "Expected a function but found a number",
+            evaluate("map(2, [1, 2])").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected a condition but found a number",
+            evaluate("filter(X -> X, [1, 2])").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Can't reduce an empty list without an initial value",
+            evaluate("reduce((a, b) -> a + b, [])")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(evaluate("map(X -> X, 2)").is_err());
+        assert!(evaluate("apply(X -> X, [1, 2])").is_err());
+        assert!(evaluate("reduce(X -> X, [1, 2])").is_err());
+    }
+
+    #[test]
+    fn rejects_unresolved_derivatives() {
+        assert_eq!(
+            "Derivatives have to be resolved before they can be evaluated",
+            evaluate("d/dX (X^2)").unwrap_err().to_string()
+        );
+        let mut env = Environment::new();
+        evaluate_with("f(X) := X^2", &mut env).unwrap();
+        assert!(evaluate_with("f'(3)", &mut env).is_err());
+        assert!(evaluate("diff(2)").is_err());
+    }
+
+    fn matrix(rows: &[&[f64]]) -> Value {
+        Value::Matrix(Matrix::from_rows(
+            rows.iter().map(|row| row.to_vec()).collect(),
+        ))
+    }
+
+    #[test]
+    fn can_eval_matrices() {
+        assert_eq!(
+            matrix(&[&[1.0, 2.0], &[3.0, 4.0]]),
+            evaluate_value("[[1, 2], [3, 4]]").unwrap()
+        );
+        assert_eq!(
+            "[[1, 2], [3, 4]]",
+            evaluate_value("[1..2, 3..4]").unwrap().to_string()
+        );
+        assert_eq!(
+            matrix(&[&[19.0, 22.0], &[43.0, 50.0]]),
+            evaluate_value("[[1, 2], [3, 4]] * [[5, 6], [7, 8]]").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[2.0, 4.0], &[6.0, 8.0]]),
+            evaluate_value("2[[1, 2], [3, 4]]").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[6.0, 8.0], &[10.0, 12.0]]),
+            evaluate_value("[[1, 2], [3, 4]] + [[5, 6], [7, 8]]").unwrap()
+        );
+        assert_eq!(
+            list(&[5.0, 11.0]),
+            evaluate_value("[[1, 2], [3, 4]] * [1, 2]").unwrap()
+        );
+        assert_eq!(
+            list(&[7.0, 10.0]),
+            evaluate_value("[1, 2] * [[1, 2], [3, 4]]").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[7.0, 10.0], &[15.0, 22.0]]),
+            evaluate_value("[[1, 2], [3, 4]]^2").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[-2.0, 1.0], &[1.5, -0.5]]),
+            evaluate_value("[[1, 2], [3, 4]]^-1").unwrap()
+        );
+        assert_eq!(
+            list(&[3.0, 4.0]),
+            evaluate_value("[[1, 2], [3, 4]][2]").unwrap()
+        );
+        assert_eq!(
+            Value::Number(2.0),
+            evaluate_value("[[1, 2], [3, 4]][1, 2]").unwrap()
+        );
+        assert!(matches!(
+            evaluate_value("[[1, 2], [3]]").unwrap(),
+            Value::List(_)
+        ));
+    }
+
+    #[test]
+    fn can_eval_linear_algebra() {
+        assert_eq!(-2.0, evaluate("det([[1, 2], [3, 4]])").unwrap());
+        assert_eq!("-3", evaluate_value("det([[1, 2], [4, 5]])").unwrap().to_string());
+        assert_eq!(
+            "-11.8",
+            evaluate_value("det([[2, 1, 3], [0.1, 5, 7], [1, 1, 1]])")
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            matrix(&[&[0.5, 1.0], &[1.5, 2.0]]),
+            evaluate_value("[[1, 2], [3, 4]] / 2").unwrap()
+        );
+        assert_eq!(
+            0.0,
+            evaluate("det([[1, 2, 3], [4, 5, 6], [7, 8, 9]])").unwrap()
+        );
+        assert_eq!(
+            -24.0,
+            evaluate("det([[0, 2, 0], [3, 0, 0], [0, 0, 4]])").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[-2.0, 1.0], &[1.5, -0.5]]),
+            evaluate_value("inv([[1, 2], [3, 4]])").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[1.0, 4.0], &[2.0, 5.0], &[3.0, 6.0]]),
+            evaluate_value("transpose([[1, 2, 3], [4, 5, 6]])").unwrap()
+        );
+        assert_eq!(
+            15.0,
+            evaluate("trace([[1, 2, 3], [4, 5, 6], [7, 8, 9]])").unwrap()
+        );
+        assert_eq!(
+            2.0,
+            evaluate("rank([[1, 2, 3], [4, 5, 6], [7, 8, 9]])").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[1.0, 0.0, -1.0], &[0.0, 1.0, 2.0]]),
+            evaluate_value("rref([[1, 2, 3], [4, 5, 6]])").unwrap()
+        );
+        assert_eq!(
+            list(&[2.0, 3.0, -1.0]),
+            evaluate_value("solve([[2, 1, -1], [-3, -1, 2], [-2, 1, 2]], [8, -11, -3])").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[1.0], &[2.0]]),
+            evaluate_value("solve([[1, 1], [1, -1]], [[3], [-1]])").unwrap()
+        );
+    }
+
+    #[test]
+    fn scales_pivot_tolerance_with_coefficients() {
+        assert_eq!(
+            list(&[1e12, 1.0]),
+            evaluate_value("solve([[1, 0], [0, 1]], [1e12, 1])").unwrap()
+        );
+        let Value::List(solution) = evaluate_value("solve([[2, 1], [1, 3]], [3e11, 4])").unwrap()
+        else {
+            panic!("expected a list");
+        };
+        for (expected, actual) in [179999999999.2, -59999999998.4].iter().zip(solution) {
+            let Value::Number(actual) = actual else {
+                panic!("expected a number");
+            };
+            assert!((expected - actual).abs() <= 1e-3);
+        }
+        assert_eq!(
+            matrix(&[&[1e20, 0.0], &[0.0, 1e20]]),
+            evaluate_value("inv([[1e-20, 0], [0, 1e-20]])").unwrap()
+        );
+        assert_eq!(2.0, evaluate("rank([[1e-20, 0], [0, 1e-20]])").unwrap());
+        assert_eq!(
+            list(&[2.0, 4.0]),
+            evaluate_value("solve([[2^-66, 0], [0, 2^-66]], [2^-65, 2^-64])").unwrap()
+        );
+        assert_eq!(0.0, evaluate("rank([[0, 0], [0, 0]])").unwrap());
+    }
+
+    #[test]
+    fn keeps_small_independent_rows() {
+        assert_eq!(
+            matrix(&[&[1e11, 0.0], &[0.0, 1.0]]),
+            evaluate_value("inv([[1e-11, 0], [0, 1]])").unwrap()
+        );
+        assert_eq!(2.0, evaluate("rank([[1e-10, 0], [0, 1]])").unwrap());
+        assert_eq!(1e-11, evaluate("det([[1e-11, 0], [0, 1]])").unwrap());
+        assert_eq!(
+            list(&[199999999999.0, -99999999999.0]),
+            evaluate_value("solve([[1e-11, 1e-11], [1, 2]], [1, 1])").unwrap()
+        );
+        assert_eq!(
+            matrix(&[&[1.0, 0.0], &[0.0, 1.0]]),
+            evaluate_value("rref([[1e-11, 0], [0, 1]])").unwrap()
+        );
+        assert_eq!(1.0, evaluate("rank([[1, 2], [2, 4]])").unwrap());
+        assert_eq!(1.0, evaluate("rank([[1e-11, 2e-11], [2e-11, 4e-11]])").unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_matrix_shapes() {
+        assert_eq!(
+            "Shapes 2×3 and 2×2 don't match",
+            evaluate_value("[[1, 2, 3], [4, 5, 6]] * [[1, 2], [3, 4]]")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Expected a square matrix but found a 2×3 matrix",
+            evaluate("det([[1, 2, 3], [4, 5, 6]])")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "The matrix is singular",
+            evaluate_value("inv([[1, 2], [2, 4]])")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Expected a matrix but found a list",
+            evaluate("det([1, 2])").unwrap_err().to_string()
+        );
+        assert!(evaluate_value("[[1, 2], [3, 4]] + [[1, 2, 3], [4, 5, 6]]").is_err());
+        assert!(evaluate_value("solve([[1, 2], [3, 4]], [1, 2, 3])").is_err());
+        assert!(evaluate_value("solve([[1, 2], [2, 4]], [1, 2])").is_err());
+        assert!(evaluate_value("[[1, 2], [3, 4]]^0.5").is_err());
+        assert_eq!(
+            "Matrices can't be divided by each other, multiply by inv(B) instead",
+            evaluate_value("[[1, 2], [3, 4]] / [[1, 2], [3, 4]]")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(evaluate_value("[[1, 2, 3], [4, 5, 6]]^2").is_err());
+    }
+
+    fn assert_close(expected: &Matrix, actual: &Matrix) {
+        assert_eq!(
+            (expected.rows(), expected.cols()),
+            (actual.rows(), actual.cols())
+        );
+        for row in 0..expected.rows() {
+            for col in 0..expected.cols() {
+                assert!(
+                    (expected.get(row, col) - actual.get(row, col)).abs() < 1e-12,
+                    "{expected} != {actual}"
+                );
+            }
+        }
+    }
+
+    fn matrices(expression: &str) -> Vec<Matrix> {
+        match evaluate_value(expression).unwrap() {
+            Value::List(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::Matrix(matrix) => matrix,
+                    Value::List(values) => Matrix::from_rows(vec![values
+                        .into_iter()
+                        .map(|value| match value {
+                            Value::Number(val) => val,
+                            value => panic!("{value} is not a number"),
+                        })
+                        .collect()]),
+                    value => panic!("{value} is not a matrix"),
+                })
+                .collect(),
+            value => panic!("{value} is not a list"),
+        }
+    }
+
+    #[test]
+    fn can_eval_eigenvalues() {
+        assert_eq!(
+            list(&[3.0, 2.0]),
+            evaluate_value("eigvals([[2, 0], [0, 3]])").unwrap()
+        );
+        assert_eq!(
+            list(&[5.0, 2.0]),
+            evaluate_value("eigvals([[4, 1], [2, 3]])").unwrap()
+        );
+        assert_eq!(
+            Value::Bool(true),
+            evaluate_value("norm(eigvals([[2, 0, 0], [1, 3, 0], [4, 5, 6]]) - [6, 3, 2]) < 1e-12")
+                .unwrap()
+        );
+        assert_eq!(
+            Value::List(vec![
+                Value::Complex(Complex::new(0.0, 1.0)),
+                Value::Complex(Complex::new(0.0, -1.0))
+            ]),
+            evaluate_value("eigvals([[0, -1], [1, 0]])").unwrap()
+        );
+        assert_eq!(
+            "[1 + 2i, 1 - 2i]",
+            evaluate_value("eigvals([[1, -2], [2, 1]])")
+                .unwrap()
+                .to_string()
+        );
+        assert!(evaluate_value("eigvals([[1, 2, 3], [4, 5, 6]])").is_err());
+    }
+
+    #[test]
+    fn can_eval_eigenvectors() {
+        let mut env = Environment::new();
+        for matrix in [
+            "[[2, 1], [1, 2]]",
+            "[[4, 1], [2, 3]]",
+            "[[1, 2, 3], [0, 4, 5], [0, 0, 6]]",
+            "[[2, -1, 0], [-1, 2, -1], [0, -1, 2]]",
+        ] {
+            evaluate_with(&format!("A := {matrix}; E := eig(A)"), &mut env).unwrap();
+            for i in 1..=len(&mut env) {
+                let check = format!("norm(A * E[2][{i}] - E[1][{i}] * E[2][{i}]) < 1e-12");
+                assert_eq!(Value::Bool(true), evaluate_with(&check, &mut env).unwrap());
+                let unit = format!("|norm(E[2][{i}]) - 1| < 1e-12");
+                assert_eq!(Value::Bool(true), evaluate_with(&unit, &mut env).unwrap());
+            }
+        }
+        assert_eq!(
+            "[[3, 1], [[0.707106781186548, 0.707106781186548], [-0.707106781186548, 0.707106781186548]]]",
+            evaluate_value("eig([[2, 1], [1, 2]])").unwrap().to_string()
+        );
+    }
+
+    fn len(env: &mut Environment) -> usize {
+        match evaluate_with("len(E[1])", env).unwrap() {
+            Value::Number(len) => len as usize,
+            value => panic!("{value} is not a length"),
+        }
+    }
+
+    #[test]
+    fn can_eval_decompositions() {
+        let a = Matrix::from_rows(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![4.0, -6.0, 0.0],
+            vec![-2.0, 7.0, 2.0],
+        ]);
+        let lu = matrices("lu([[2, 1, 1], [4, -6, 0], [-2, 7, 2]])");
+        assert_close(&lu[2].mul(&a).unwrap(), &lu[0].mul(&lu[1]).unwrap());
+        assert_eq!(1.0, lu[0].get(1, 1));
+        assert_eq!(0.0, lu[1].get(2, 0));
+
+        let qr = matrices("qr([[2, 1, 1], [4, -6, 0], [-2, 7, 2]])");
+        assert_close(&a, &qr[0].mul(&qr[1]).unwrap());
+        assert_close(
+            &Matrix::identity(3),
+            &qr[0].transpose().mul(&qr[0]).unwrap(),
+        );
+        assert!((0..3).all(|i| qr[1].get(i, i) >= 0.0));
+
+        assert_eq!(
+            matrix(&[&[2.0, 0.0], &[1.0, 3.0]]),
+            evaluate_value("cholesky([[4, 2], [2, 10]])").unwrap()
+        );
+        assert_eq!(
+            "The matrix is not symmetric positive definite",
+            evaluate_value("cholesky([[1, 2], [2, 1]])")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let rectangular = Matrix::from_rows(vec![vec![3.0, 2.0, 2.0], vec![2.0, 3.0, -2.0]]);
+        let svd = matrices("svd([[3, 2, 2], [2, 3, -2]])");
+        assert_close(&Matrix::from_rows(vec![vec![5.0, 3.0]]), &svd[1]);
+        let mut scaled = svd[0].clone();
+        for row in 0..2 {
+            for col in 0..2 {
+                scaled.set(row, col, scaled.get(row, col) * svd[1].get(0, col));
+            }
+        }
+        assert_close(&rectangular, &scaled.mul(&svd[2].transpose()).unwrap());
+        assert_eq!(
+            list(&[5.0, 0.0]),
+            evaluate_value("svd([[1, 2], [2, 4]])[2]").unwrap()
+        );
+    }
 }
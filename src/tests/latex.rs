@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod test {
+    use crate::error::error_span;
+    use crate::numeric_evaluator::evaluate_latex;
+    use crate::parser::{parse, parse_latex, parse_latex_with_config, ParseConfig};
+
+    fn setup(latex: &str, plain: &str) {
+        let expected = parse(plain).unwrap();
+        let actual = parse_latex(latex).unwrap();
+        assert!(
+            expected.same_shape(&actual),
+            "{latex}: {expected} != {actual}"
+        );
+    }
+
+    #[test]
+    fn can_parse_fractions() {
+        setup("\\frac{1}{2}", "1/2");
+        setup("\\frac{x+1}{2y}", "(x+1)/(2y)");
+        setup("\\frac12", "1/2");
+        setup("\\dfrac{\\frac{1}{2}}{3}", "(1/2)/3");
+        setup("\\frac{1}{2}x", "(1/2)x");
+    }
+
+    #[test]
+    fn can_parse_roots() {
+        setup("\\sqrt{x}", "sqrt(x)");
+        setup("\\sqrt2", "sqrt(2)");
+        setup("\\sqrt[3]{x+1}", "root(x+1, 3)");
+        setup("2\\sqrt{2}", "2sqrt(2)");
+    }
+
+    #[test]
+    fn can_parse_operators() {
+        setup("3\\cdot 4", "3*4");
+        setup("3\\times4-1", "3*4-1");
+        setup("6\\div 2", "6/2");
+        setup("50\\%", "50%");
+        setup("\\left(1+2\\right)\\cdot3!", "(1+2)*3!");
+        setup("\\left[1+2\\right]", "(1+2)");
+        setup("\\left|x-3\\right|", "|x-3|");
+    }
+
+    #[test]
+    fn can_parse_powers() {
+        setup("x^{2}", "x^2");
+        setup("2x^2", "2x^2");
+        setup("x^{n+1}", "x^(n+1)");
+        setup("2^{10}", "2^10");
+        setup("e^{-x}", "e^(-x)");
+        setup("\\left(1+2\\right)^{3}", "(1+2)^3");
+    }
+
+    #[test]
+    fn can_parse_names() {
+        setup("\\pi", "pi");
+        setup("2\\pi r", "2pi r");
+        setup("\\infty", "inf");
+        setup("xy", "x y");
+        setup("x_1+x_{12}", "x_1+x_12");
+        setup("\\theta", "theta");
+        setup("\\operatorname{floor}\\left(2.5\\right)", "floor(2.5)");
+    }
+
+    #[test]
+    fn can_parse_functions() {
+        setup("\\sin\\left(\\pi\\right)", "sin(pi)");
+        setup("\\sin(30)", "sin(30)");
+        setup("\\sin 30", "sin(30)");
+        setup("\\sin 2x", "sin(2x)");
+        setup("\\sin^2 x", "sin(x)^2");
+        setup("\\max\\left(1, 2\\right)", "max(1, 2)");
+        setup("\\cos\\,\\sin x", "cos(sin(x))");
+        setup("\\ln x", "ln(x)");
+        setup("\\log\\left(100\\right)", "log(100)");
+        setup("\\exp 2x", "exp(2x)");
+        setup("\\sin30\\cos60", "sin(30)*cos(60)");
+        setup("\\sin 2x\\cos x", "sin(2x)*cos(x)");
+        setup("\\sin30\\cos^2 60", "sin(30)*cos(60)^2");
+    }
+
+    #[test]
+    fn rejects_invalid_latex() {
+        assert!(parse_latex("\\frac{1}").is_err());
+        assert!(parse_latex("\\sin").is_err());
+        assert!(parse_latex("\\left(1+2").is_err());
+
+        let err = parse_latex("(1, 2)").unwrap_err();
+        assert_eq!(2..3, error_span(&err).unwrap().range());
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        let err = parse_latex("\\foo").unwrap_err();
+        assert_eq!("Syntax error: unknown command '\\foo'", err.to_string());
+        assert_eq!(0..4, error_span(&err).unwrap().range());
+
+        let err = parse_latex("2 + \\alpha_1\\bar{x}").unwrap_err();
+        assert_eq!(12..16, error_span(&err).unwrap().range());
+        assert!(parse_latex("\\frac{1}{\\foo}").is_err());
+        assert!(parse_latex("\\alpha + \\Omega").is_ok());
+    }
+
+    #[test]
+    fn can_eval_latex() {
+        assert_eq!(0.5, evaluate_latex("\\frac{1}{2}").unwrap());
+        assert_eq!(-2.0, evaluate_latex("\\sqrt[3]{-8}").unwrap());
+        assert_eq!(1.0, evaluate_latex("\\sin^2 30 + \\cos^2 30").unwrap());
+        assert_eq!(12.0, evaluate_latex("3\\cdot\\left(1+3\\right)").unwrap());
+        assert_eq!(f64::INFINITY, evaluate_latex("\\infty").unwrap());
+        assert_eq!(1.0, evaluate_latex("\\ln e").unwrap());
+        assert_eq!(2.0, evaluate_latex("\\log 100").unwrap());
+        assert_eq!(0.25, evaluate_latex("\\sin30\\cos60").unwrap());
+    }
+
+    #[test]
+    fn can_parse_with_separators() {
+        let german = ParseConfig::new(',', ';', Some('.')).unwrap();
+        let expr = parse_latex_with_config("\\frac{1.000,5}{2}", &german).unwrap();
+        assert!(parse("1000.5/2").unwrap().same_shape(&expr), "{expr}");
+        let expr = parse_latex_with_config("\\max\\left(1,5; 2\\right)", &german).unwrap();
+        assert!(parse("max(1.5, 2)").unwrap().same_shape(&expr), "{expr}");
+    }
+
+    #[test]
+    fn can_parse_relations() {
+        setup("0\\lt x\\le 5", "0 < x <= 5");
+        setup("x\\geq 2", "x >= 2");
+        setup("x\\neq 2", "x != 2");
+    }
+}
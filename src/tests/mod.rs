@@ -1,4 +1,10 @@
+#![allow(clippy::approx_constant)]
+
 mod parser;
 mod optimizer;
 mod round;
-mod evaluator;
\ No newline at end of file
+mod evaluator;
+mod span;
+mod operators;
+mod latex;
+mod tokenizer;
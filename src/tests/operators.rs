@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod test {
+    use crate::error::{error_span, ParserError};
+    use crate::numeric_evaluator::{evaluate_statements, Environment, Value};
+    use crate::parser::{
+        parse, parse_with_operators, precedence, Assoc, Op, Operator, OperatorFn, OperatorRegistry,
+        Semantics, Statement,
+    };
+
+    fn setup(expression: &str, registry: &OperatorRegistry) -> String {
+        parse_with_operators(expression, registry)
+            .unwrap()
+            .to_string()
+    }
+
+    fn unicode_registry() -> OperatorRegistry {
+        let mut registry = OperatorRegistry::default();
+        registry.add(Operator::infix(
+            "×",
+            precedence::MULTIPLICATIVE,
+            Assoc::Left,
+            Semantics::Binary(Op::Multiply),
+        ));
+        registry.add(Operator::infix(
+            "÷",
+            precedence::MULTIPLICATIVE,
+            Assoc::Left,
+            Semantics::Binary(Op::Divide),
+        ));
+        registry
+    }
+
+    #[test]
+    fn can_parse_operator_aliases() {
+        let registry = unicode_registry();
+        assert_eq!("(2*3)", setup("2×3", &registry));
+        assert_eq!("((1+(2*3))/4)", setup("(1+2×3)÷4", &registry));
+        assert_eq!("((6/2)*-(3))", setup("6÷2×-3", &registry));
+    }
+
+    #[test]
+    fn can_parse_keyword_operators() {
+        let mut registry = OperatorRegistry::default();
+        registry.add(Operator::infix(
+            "mod",
+            precedence::MULTIPLICATIVE,
+            Assoc::Left,
+            Semantics::Binary(Op::Modulo),
+        ));
+        assert_eq!("(7%3)", setup("7 mod 3", &registry));
+        assert_eq!("(1+(2X^(1)%3))", setup("1 + 2X mod 3", &registry));
+        assert_eq!("1modulo^(1)", setup("modulo", &registry));
+    }
+
+    #[test]
+    fn can_parse_custom_operators() {
+        let function = OperatorFn::new(|operands| operands[0] * 10.0 + operands[1]);
+        let mut registry = OperatorRegistry::default();
+        registry.add(Operator::infix(
+            "⊕",
+            precedence::ADDITIVE,
+            Assoc::Right,
+            Semantics::Custom(function.clone()),
+        ));
+        registry.add(Operator::prefix(
+            "~",
+            precedence::PREFIX,
+            Semantics::Function("round".to_string()),
+        ));
+        registry.add(Operator::postfix(
+            "°",
+            precedence::POSTFIX,
+            Semantics::Custom(function),
+        ));
+        assert_eq!("(1⊕(2⊕3))", setup("1⊕2⊕3", &registry));
+        assert_eq!("((1*2)⊕3)", setup("1*2⊕3", &registry));
+        assert_eq!("(round(1.5)+2)", setup("~1.5+2", &registry));
+        assert_eq!("(2*round(3))", setup("2~3", &registry));
+        assert!(parse_with_operators("30°", &registry).is_ok());
+    }
+
+    #[test]
+    fn empty_registry_has_no_operators() {
+        assert_eq!("(1+2)", setup("1+2", &OperatorRegistry::default()));
+        assert!(parse_with_operators("1+2", &OperatorRegistry::empty()).is_err());
+    }
+
+    fn setup_eval(expression: &str, registry: &OperatorRegistry) -> Value {
+        let expr = parse_with_operators(expression, registry).unwrap();
+        evaluate_statements(vec![Statement::Expression(expr)], &mut Environment::new()).unwrap()
+    }
+
+    #[test]
+    fn can_eval_registered_operators() {
+        let mut registry = OperatorRegistry::default();
+        registry.add(Operator::infix(
+            "⊗",
+            precedence::MULTIPLICATIVE,
+            Assoc::Left,
+            Semantics::Custom(OperatorFn::new(|operands| operands[0] * operands[1] + 1.0)),
+        ));
+        registry.add(Operator::prefix(
+            "~",
+            precedence::PREFIX,
+            Semantics::Function("round".to_string()),
+        ));
+        assert_eq!(Value::Number(7.0), setup_eval("2⊗3", &registry));
+        assert_eq!(Value::Number(8.0), setup_eval("1 + 2⊗3", &registry));
+        assert_eq!(Value::Number(8.0), setup_eval("2~3.6", &registry));
+        assert_eq!(Value::Number(9.0), setup_eval("~(4.4+5)", &registry));
+        assert_eq!("(2⊗3)", setup("2⊗3", &registry));
+        assert!(parse("2⊗3").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_operators() {
+        let err = parse("2 # 3").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ParserError>(),
+            Some(ParserError::UnknownOperator(symbol, _)) if symbol == "#"
+        ));
+        assert_eq!(2..3, error_span(&err).unwrap().range());
+        assert_eq!(
+            2..3,
+            error_span(&parse("2*#3").unwrap_err()).unwrap().range()
+        );
+    }
+}
@@ -136,4 +136,80 @@ mod test {
         assert_eq!("12X^(10)", setup_single("2X^8*6X^2"));
         assert_eq!("1X^(2)", setup_single("X*X"));
     }
+
+    #[test]
+    fn can_optimize_multivariate_monomials() {
+        assert_eq!("3X^(1)Y^(2)", setup_single("XY^2 + 2YXY"));
+        assert_eq!("6X^(3/2)Y^(1)", setup_single("2X^(1/2) * 3XY"));
+        assert_eq!("1Y^(1)", setup_single("X^2Y * X^-2"));
+        assert_eq!("5", setup_single("5X^-1 * X"));
+        assert_eq!("(1X^(1)Y^(1)+1X^(1))", setup_single("XY + X"));
+    }
+
+    #[test]
+    fn can_optimize_piecewise() {
+        assert_eq!("2", setup_single("if(1 < 2, 2, 3)"));
+        assert_eq!("3", setup_single("if(1 > 2, 2, 3)"));
+        assert_eq!(
+            "piecewise((1, (1X^(1)<0)), 2)",
+            setup_single("piecewise((1, X < 0), (2, 1 < 2), (3, X > 5))")
+        );
+        assert_eq!(
+            "piecewise((2, (1X^(1)<5)), 3)",
+            setup_single("piecewise((1, 5 < 0), (2, X < 5), 3)")
+        );
+        assert_eq!(
+            "piecewise((1, (2<1)))",
+            setup_single("piecewise((1, 2 < 1))")
+        );
+        assert_eq!("sin(1X^(1))", setup_single("if(1 < 2, sin(X + 0), 2)"));
+    }
+
+    #[test]
+    fn can_optimize_series() {
+        assert_eq!("(3*4)", setup_single("sum(k, 1, 4, 3)"));
+        assert_eq!("(3^4)", setup_single("prod(k, 1, 4, 3)"));
+        assert_eq!("0", setup_multi("sum(k, 5, 1, 3)"));
+        assert_eq!(
+            "sum(k, 1, 1n^(1), 1k^(2))",
+            setup_multi("sum(k, 0+1, n, k*k)")
+        );
+        assert_eq!(
+            "sum(k, 1, 3, sin(1k^(1)))",
+            setup_single("sum(k, 1, 3, sin(k * 1))")
+        );
+        assert_eq!(
+            "sum(k, 1, 2000000, 1)",
+            setup_multi("sum(k, 1, 2000000, 1)")
+        );
+        assert_eq!(
+            "prod(k, 1, 100000000000000000000, 2)",
+            setup_multi("prod(k, 1, 1e20, 2)")
+        );
+        assert_eq!("(1*1000001)", setup_single("sum(k, 0, 1000000, 1)"));
+    }
+
+    #[test]
+    fn can_optimize_lambdas() {
+        assert_eq!("((X) -> 1X^(1))", setup_single("X -> X + 0"));
+    }
+
+    #[test]
+    fn can_optimize_logic() {
+        assert_eq!("false", setup_single("not (1 < 2)"));
+        assert_eq!("true", setup_single("true and 2 > 1"));
+        assert_eq!("(1X^(1) or true)", setup_single("X or not false"));
+    }
+
+    #[test]
+    fn can_optimize_sets() {
+        assert_eq!("(1X^(1) ∈ [1X^(1), 2])", setup_single("X in [X + 0, 2]"));
+        assert_eq!("({1X^(1)} ∪ (0, 1))", setup_single("{X * 1} ∪ (0, 1)"));
+    }
+
+    #[test]
+    fn can_optimize_derivatives() {
+        assert_eq!("diff(1X^(1), X, 2)", setup_single("d^2/dX^2 (X + 0)"));
+        assert_eq!("f'(2)", setup_single("f'(2)"));
+    }
 }
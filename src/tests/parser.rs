@@ -1,12 +1,26 @@
-
 #[cfg(test)]
 mod test {
-    use crate::parser::{parse, parse_equation};
+    use crate::error::error_span;
+    use crate::parser::{
+        parse, parse_equation, parse_statements, parse_statements_with_config, parse_tolerant,
+        parse_tolerant_with_config, parse_with_config, Expr, ParseConfig, Statement,
+    };
 
     fn setup_basic(expression: &str) -> String {
         parse(expression).unwrap().to_string()
     }
 
+    /// The partial tree and the span of every diagnostic.
+    fn setup_tolerant(expression: &str) -> (String, Vec<(usize, usize)>) {
+        let partial = parse_tolerant(expression);
+        let spans = partial
+            .diagnostics
+            .iter()
+            .map(|err| (err.span().start, err.span().end))
+            .collect();
+        (partial.expr.to_string(), spans)
+    }
+
     fn setup_equation(expression: &str) -> String {
         parse_equation(expression).unwrap().to_string()
     }
@@ -103,6 +117,42 @@ mod test {
         assert_eq!("1B^(1)", setup_basic("B"));
     }
 
+    #[test]
+    fn can_parse_multivariate_monomials() {
+        assert_eq!("3X^(2)Y^(1)", setup_basic("3X^2Y"));
+        assert_eq!("1X^(1)Y^(1)", setup_basic("XY"));
+        assert_eq!("2X^(1)Y^(2)Z^(1)", setup_basic("2XY^2Z"));
+        assert_eq!("1X^(3)Y^(1)", setup_basic("YX^2X"));
+        assert_eq!("1X^(2)Y^(1)", setup_basic("X²Y"));
+        assert_eq!("1nand^(1)", setup_basic("nand"));
+        assert_eq!("(1X^(2)*e)", setup_basic("X^2e"));
+        assert_eq!("(1X^(2)*sin(30))", setup_basic("X^2sin(30)"));
+    }
+
+    #[test]
+    fn can_parse_rational_exponents() {
+        assert_eq!("1X^(1/2)", setup_basic("X^(1/2)"));
+        assert_eq!("1X^(-1)", setup_basic("X^-1"));
+        assert_eq!("1X^(-2/3)", setup_basic("X^(-4/6)"));
+        assert_eq!("1X^(1/2)", setup_basic("X^0.5"));
+        assert_eq!("1X^(1/3)", setup_basic("X^0.(3)"));
+        assert_eq!("(1X^(1)^(1a^(1)+1))", setup_basic("X^(a+1)"));
+        assert!(parse("X^(1/0)").is_err());
+        assert!(parse("X^1e300").is_ok());
+    }
+
+    #[test]
+    fn keeps_other_exponents_as_powers() {
+        assert_eq!("(1X^(1)^1.2345678)", setup_basic("X^1.2345678"));
+        assert_eq!("(1X^(1)^100000000000000000000)", setup_basic("X^1e20"));
+        assert_eq!("(1Y^(1)^-100000000000000000000)", setup_basic("Y^-1e20"));
+        assert_eq!(
+            "((2*(1X^(1)^1.2345678))*1Y^(1))",
+            setup_basic("2X^1.2345678Y")
+        );
+        assert_eq!("(2^1.2345678)", setup_basic("2^1.2345678"));
+    }
+
     #[test]
     fn can_parse_equations() {
         assert_eq!("((1+1)=(4-2))", setup_equation("1+1=4-2"))
@@ -115,4 +165,526 @@ mod test {
         assert_eq!("phi", setup_basic("phi"));
         assert_eq!("e", setup_basic("e"));
     }
+
+    #[test]
+    fn can_parse_identifiers() {
+        assert_eq!("1x^(1)", setup_basic("x"));
+        assert_eq!("2theta^(3)", setup_basic("2theta^3"));
+        assert_eq!("1v_0^(1)", setup_basic("v_0"));
+        assert_eq!("(1Vmax^(1)-1x^(1))", setup_basic("Vmax - x"));
+        assert_eq!("-(1x^(1))", setup_basic("-x"));
+    }
+
+    #[test]
+    fn can_resolve_constants_in_monomials() {
+        assert_eq!("(2*pi)", setup_basic("2pi"));
+        assert_eq!("(2*(pi^2))", setup_basic("2pi^2"));
+    }
+
+    #[test]
+    fn can_parse_negated_functions() {
+        assert_eq!("-(sin(30))", setup_basic("-sin(30)"));
+    }
+
+    #[test]
+    fn rejects_functions_without_arguments() {
+        assert!(parse("sin").is_err());
+        assert!(parse("2 + cos").is_err());
+    }
+
+    #[test]
+    fn can_parse_implicit_multiplication() {
+        assert_eq!("(2*(3+4))", setup_basic("2(3+4)"));
+        assert_eq!("((1a^(1)+1)*(1a^(1)-1))", setup_basic("(a+1)(a-1)"));
+        assert_eq!("(3*sin(30))", setup_basic("3sin(30)"));
+        assert_eq!("(pi*1X^(1))", setup_basic("pi X"));
+        assert_eq!("(pi*2)", setup_basic("pi(2)"));
+        assert_eq!(setup_basic("2*(3+4)"), setup_basic("2(3+4)"));
+    }
+
+    #[test]
+    fn implicit_multiplication_binds_tighter_than_division() {
+        assert_eq!("(1/2X^(1))", setup_basic("1/2X"));
+        assert_eq!("(1/(2*1X^(1)))", setup_basic("1/2(X)"));
+        assert_eq!("(1/(2*pi))", setup_basic("1/2pi"));
+        assert_eq!("((2^3)*4)", setup_basic("2^3(4)"));
+    }
+
+    #[test]
+    fn rejects_ambiguous_juxtaposition() {
+        assert!(parse("2 3").is_err());
+        assert_eq!("(2-3)", setup_basic("2 -3"));
+    }
+
+    #[test]
+    fn can_parse_scientific_notation() {
+        assert_eq!("602200000000000000000000", setup_basic("6.022e23"));
+        assert_eq!("0.000000001", setup_basic("1E-9"));
+        assert_eq!("150", setup_basic("1.5e+2"));
+        assert_eq!("(2*e)", setup_basic("2e"));
+    }
+
+    #[test]
+    fn can_parse_leading_dot_decimals() {
+        assert_eq!("0.5", setup_basic(".5"));
+        assert_eq!("(2*0.25)", setup_basic("2*.25"));
+    }
+
+    #[test]
+    fn can_parse_radix_literals() {
+        assert_eq!("255", setup_basic("0xFF"));
+        assert_eq!("10", setup_basic("0b1010"));
+        assert_eq!("15", setup_basic("0o17"));
+        assert_eq!("65535", setup_basic("0xff_ff"));
+    }
+
+    #[test]
+    fn can_parse_large_radix_literals() {
+        fn value(expression: &str) -> f64 {
+            match parse(expression).unwrap() {
+                Expr::Number(value, _) => value,
+                expr => panic!("expected a number, found {expr}"),
+            }
+        }
+        assert_eq!(2f64.powi(64), value("0x1_0000_0000_0000_0000"));
+        assert_eq!(2f64.powi(100), value(&format!("0b1{}", "0".repeat(100))));
+        assert_eq!(8f64.powi(30), value(&format!("0o1{}", "0".repeat(30))));
+        assert_eq!(16f64.powi(40), value(&format!("0x1{}", "0".repeat(40))));
+        assert_eq!(
+            value("340282366920938463463374607431768211456"),
+            value("0x1_0000_0000_0000_0000_0000_0000_0000_0000")
+        );
+        assert_eq!(f64::INFINITY, value(&format!("0x1{}", "0".repeat(300))));
+    }
+
+    #[test]
+    fn can_parse_digit_separators() {
+        assert_eq!("1000000", setup_basic("1_000_000"));
+        assert_eq!("1234.5678", setup_basic("1_234.567_8"));
+        assert!(parse("1__000").is_err());
+    }
+
+    #[test]
+    fn can_parse_factorials() {
+        assert_eq!("(5)!", setup_basic("5!"));
+        assert_eq!("(5)!!", setup_basic("5!!"));
+        assert_eq!("((2+1))!", setup_basic("(2+1)!"));
+        assert_eq!("-((3)!)", setup_basic("-3!"));
+        assert_eq!("(2^(3)!)", setup_basic("2^3!"));
+    }
+
+    #[test]
+    fn can_parse_percentages() {
+        assert_eq!("(10)%", setup_basic("10%"));
+        assert_eq!("(50+(10)%)", setup_basic("50 + 10%"));
+        assert_eq!("((10)%*2)", setup_basic("10% * 2"));
+        assert_eq!("(10%3)", setup_basic("10 % 3"));
+        assert_eq!("(10%-(3))", setup_basic("10%-3"));
+    }
+
+    #[test]
+    fn can_parse_absolute_value_bars() {
+        assert_eq!("abs((1X^(1)-3))", setup_basic("|X - 3|"));
+        assert_eq!("(abs(1a^(1))+abs(1b^(1)))", setup_basic("|a| + |b|"));
+        assert_eq!("(2*abs(1X^(1)))", setup_basic("2|X|"));
+        assert_eq!("abs((abs(-(3))-5))", setup_basic("||-3| - 5|"));
+        assert_eq!(setup_basic("abs(X - 3)"), setup_basic("|X - 3|"));
+    }
+
+    #[test]
+    fn can_parse_norm_bars() {
+        assert_eq!("norm(1v^(1))", setup_basic("||v||"));
+        assert_eq!("(norm(1v^(1))^2)", setup_basic("||v||^2"));
+    }
+
+    #[test]
+    fn can_parse_unicode_symbols() {
+        assert_eq!("(2*3)", setup_basic("2×3"));
+        assert_eq!("((2*3)/4)", setup_basic("2·3÷4"));
+        assert_eq!("(5-3)", setup_basic("5−3"));
+        assert_eq!("-(3)", setup_basic("−3"));
+        assert_eq!("sqrt(1X^(1))", setup_basic("√X"));
+        assert_eq!("(2*cbrt(8))", setup_basic("2∛8"));
+        assert_eq!("(1X^(1)≤2)", setup_basic("X ≤ 2"));
+        assert_eq!("(1X^(1)≥2)", setup_basic("X≥2"));
+        assert_eq!("(1X^(1)≠2)", setup_basic("X ≠ 2"));
+    }
+
+    #[test]
+    fn can_parse_unicode_constants() {
+        assert_eq!("π", setup_basic("π"));
+        assert_eq!("(2*π)", setup_basic("2π"));
+        assert_eq!("((2*π)*1r^(1))", setup_basic("2πr"));
+        assert_eq!("(τ+φ)", setup_basic("τ+φ"));
+    }
+
+    #[test]
+    fn can_parse_superscripts() {
+        assert_eq!("1X^(2)", setup_basic("X²"));
+        assert_eq!("3X^(-1)", setup_basic("3X⁻¹"));
+        assert_eq!("1X^(10)", setup_basic("X¹⁰"));
+        assert_eq!("((1+2)^3)", setup_basic("(1+2)³"));
+        assert_eq!("(π^2)", setup_basic("π²"));
+        assert_eq!("(2^-4)", setup_basic("2⁻⁴"));
+    }
+
+    #[test]
+    fn can_parse_relations() {
+        assert_eq!("(1X^(1)<3)", setup_basic("X < 3"));
+        assert_eq!("((1+2)≥3)", setup_basic("1 + 2 >= 3"));
+        assert_eq!("(1X^(1)≠2)", setup_basic("X != 2"));
+        assert_eq!("(0<1X^(1)≤5)", setup_basic("0 < X <= 5"));
+        assert_eq!("((1<2)=1)", setup_basic("(1 < 2) = 1"));
+        assert_eq!("(2>1X^(1)>(-(3)*2))", setup_basic("2 > X > -3*2"));
+    }
+
+    #[test]
+    fn rejects_invalid_equations() {
+        assert!(parse_equation("X < 3").is_err());
+        assert!(parse_equation("1 = 2 = 3").is_err());
+        assert!(parse_equation("1 < X = 3").is_err());
+        assert_eq!("(1X^(1)=(1<2))", setup_equation("X = (1 < 2)"));
+    }
+
+    #[test]
+    fn can_parse_statements() {
+        let statements = parse_statements("a := 3; b := a^2\n\nb + 1;").unwrap();
+        assert_eq!(3, statements.len());
+        assert!(
+            matches!(&statements[0], Statement::Assignment { name, value, .. } if name == "a" && value.to_string() == "3")
+        );
+        assert!(matches!(&statements[1], Statement::Assignment { name, .. } if name == "b"));
+        assert!(
+            matches!(&statements[2], Statement::Expression(expr) if expr.to_string() == "(1b^(1)+1)")
+        );
+        assert_eq!(1, parse_statements("\t2 * 3\r\n").unwrap().len());
+    }
+
+    #[test]
+    fn rejects_invalid_statements() {
+        assert!(parse_statements("").is_err());
+        assert!(parse_statements("pi := 3").is_err());
+        assert!(parse_statements("sin := 3").is_err());
+        assert!(parse_statements("a := ").is_err());
+        assert!(parse_statements("1 := 2").is_err());
+        assert!(parse("a := 3").is_err());
+    }
+
+    #[test]
+    fn can_parse_function_definitions() {
+        let statements = parse_statements("g(a, b) := sqrt(a^2+b^2); g(3, 4)").unwrap();
+        assert!(matches!(
+            &statements[0],
+            Statement::Definition { name, params, body, .. }
+                if name == "g" && params == &["a", "b"] && body.to_string() == "sqrt((1a^(2)+1b^(2)))"
+        ));
+        assert!(matches!(
+            &statements[1],
+            Statement::Expression(Expr::Function { .. })
+        ));
+        assert!(matches!(
+            &parse_statements("k() := 3").unwrap()[0],
+            Statement::Definition { params, .. } if params.is_empty()
+        ));
+
+        assert!(parse_statements("sin(X) := X").is_err());
+        assert!(parse_statements("f(pi) := pi").is_err());
+        assert!(parse_statements("f(a, a) := a").is_err());
+        assert!(parse_statements("f(2) := 3").is_err());
+    }
+
+    #[test]
+    fn can_parse_lists() {
+        assert_eq!("[1, (2+3), 1X^(1)]", setup_basic("[1, 2 + 3, X]"));
+        assert_eq!("[]", setup_basic("[ ]"));
+        assert_eq!("([1, 2]*2)", setup_basic("[1, 2] * 2"));
+        assert_eq!("(2*[1, 2])", setup_basic("2[1, 2]"));
+        assert_eq!("(1v^(1))[2]", setup_basic("v[2]"));
+        assert_eq!("-((1v^(1))[(1+1)])", setup_basic("-v[1+1]"));
+        assert_eq!("(([1, 2])[1])[1]", setup_basic("[1, 2][1][1]"));
+        assert_eq!("range(1, 10)", setup_basic("1..10"));
+        assert_eq!("range(0.5, (1n^(1)+1))", setup_basic(".5..n+1"));
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("[1,, 2]").is_err());
+    }
+
+    #[test]
+    fn can_parse_piecewise() {
+        assert_eq!(
+            "piecewise(((1X^(1)*2), (1X^(1)<3)), 0)",
+            setup_basic("if(X < 3, X*2, 0)")
+        );
+        assert_eq!(
+            "piecewise((1, (1X^(1)<0)), (2, (1X^(1)<5)), 3)",
+            setup_basic("piecewise((1, X < 0), (2, X < 5), 3)")
+        );
+        assert_eq!(
+            "piecewise((1X^(1), (1X^(1)>0)))",
+            setup_basic("piecewise((X, X > 0))")
+        );
+        assert_eq!(
+            "(2*piecewise((1, (1a^(1)=1)), 0))",
+            setup_basic("2if(a = 1, 1, 0)")
+        );
+        // `(1, 2)` is an open interval
+        assert!(parse("(1, 2, 3)").is_err());
+        assert!(parse("piecewise(1)").is_err());
+        assert!(parse("piecewise(0, (1, X > 0))").is_err());
+        assert!(parse("piecewise((1, X > 0, 2))").is_err());
+    }
+
+    #[test]
+    fn can_parse_series() {
+        assert_eq!(
+            "sum(k, 1, 1n^(1), (1/1k^(2)))",
+            setup_basic("sum(k, 1, n, 1/k^2)")
+        );
+        assert_eq!(
+            "prod(i, 1, inf, (1+1i^(1)))",
+            setup_basic("prod(i, 1, inf, 1 + i)")
+        );
+        assert_eq!("sum([1, 2])", setup_basic("sum([1, 2])"));
+        assert!(parse("sum(2k, 1, 3, k)").is_err());
+        assert!(parse("sum(pi, 1, 3, 1)").is_err());
+    }
+
+    #[test]
+    fn can_parse_lambdas() {
+        assert_eq!("((X) -> (1X^(2)+1))", setup_basic("X -> X^2 + 1"));
+        assert_eq!("((a, b) -> (1a^(1)*1b^(1)))", setup_basic("(a, b) -> a*b"));
+        assert_eq!("((X) -> ((Y) -> 1Y^(1)))", setup_basic("X → Y -> Y"));
+        assert_eq!(
+            "map(((X) -> 2X^(1)), [1, 2])",
+            setup_basic("map(X -> 2X, [1, 2])")
+        );
+        assert!(parse("2X -> X").is_err());
+        assert!(parse("(a, 2) -> a").is_err());
+        assert!(parse("(a, a) -> a").is_err());
+        assert!(parse("(a, b, c) + 1").is_err());
+    }
+
+    #[test]
+    fn can_parse_logic() {
+        assert_eq!("(true and false)", setup_basic("true and false"));
+        assert_eq!(
+            "((1a^(1) and 1b^(1)) or (1c^(1) and 1d^(1)))",
+            setup_basic("a and b or c and d")
+        );
+        assert_eq!(
+            "((1a^(1) or 1b^(1)) implies (1c^(1) implies 1d^(1)))",
+            setup_basic("a or b implies c implies d")
+        );
+        assert_eq!(
+            "(not((1X^(1)<3)) xor (1X^(1)>5))",
+            setup_basic("not X < 3 xor X > 5")
+        );
+        assert_eq!(
+            "(1a^(1) and not((1b^(1) or 1c^(1))))",
+            setup_basic("a and not (b or c)")
+        );
+        assert!(parse("2true").is_err());
+        assert!(parse("true^2").is_err());
+        assert!(parse("and").is_err());
+    }
+
+    #[test]
+    fn can_parse_sets() {
+        assert_eq!("[0, 1)", setup_basic("[0, 1)"));
+        assert_eq!("(-(inf), 3]", setup_basic("(-inf, 3]"));
+        assert_eq!("{1, 2, 3}", setup_basic("{1, 2, 3}"));
+        assert_eq!("{}", setup_basic("{}"));
+        assert_eq!(
+            "(1X^(1) ∈ ((-(inf), -(2)) ∪ (2, inf)))",
+            setup_basic("X in (-inf, -2) ∪ (2, inf)")
+        );
+        assert_eq!(
+            "([0, 1] ∪ ({5} ∩ (0, 5]))",
+            setup_basic("[0, 1] union {5} intersect (0, 5]")
+        );
+        assert_eq!("([0, 2] \\ {1})", setup_basic("[0, 2] \\ {1}"));
+        assert_eq!("[1, 2, 3]", setup_basic("[1, 2, 3]"));
+        assert!(parse("[0, 1, 2)").is_err());
+        assert!(parse("{1, 2").is_err());
+    }
+
+    #[test]
+    fn can_parse_derivatives() {
+        assert_eq!("diff(1X^(2), X)", setup_basic("d/dX (X^2)"));
+        assert_eq!("(diff(2X^(2), X)+1)", setup_basic("d/dX 2X^2 + 1"));
+        assert_eq!("diff(1X^(3), X, 2)", setup_basic("d^2/dX^2 (X^3)"));
+        assert_eq!("diff(1X^(1), X, 2)", setup_basic("d²/dX² X"));
+        assert_eq!("diff(1X^(2), X, 2)", setup_basic("diff(X^2, X, 2)"));
+        assert_eq!("diff(sin(1t^(1)), t)", setup_basic("diff(sin(t), t)"));
+        assert_eq!("f'(1X^(1))", setup_basic("f'(X)"));
+        assert_eq!("(f''(2)*3)", setup_basic("f''(2) * 3"));
+        assert!(matches!(
+            parse("d/dX (X)").unwrap(),
+            Expr::Derivative { order: 1, .. }
+        ));
+        assert!(parse("d^2/dX (X)").is_err());
+        assert!(parse("d^0/dX^0 (X)").is_err());
+        assert!(parse("d/dπ (X)").is_err());
+        assert!(parse("d/de (X)").is_err());
+        assert!(parse("diff(X, 2)").is_err());
+        assert!(parse("diff(X, X, 0)").is_err());
+        assert!(parse("g'(1, 2)").is_err());
+    }
+
+    #[test]
+    fn can_parse_functions_without_parameters() {
+        assert_eq!("(f()+1)", setup_basic("f() + 1"));
+        assert_eq!("(2*f())", setup_basic("2f()"));
+        let statements = parse_statements("f() := 3; f()").unwrap();
+        assert!(matches!(
+            &statements[0],
+            Statement::Definition { params, .. } if params.is_empty()
+        ));
+        assert!(parse_tolerant("f()").diagnostics.is_empty());
+        assert_eq!(
+            "Syntax error: function 'sin' is missing its arguments",
+            parse("sin()").unwrap_err().to_string()
+        );
+        assert!(!parse_tolerant("max()").diagnostics.is_empty());
+    }
+
+    #[test]
+    fn rejects_primes_on_builtin_functions() {
+        for expression in ["sin'(X)", "2 + sqrt''(X)", "max'(1, 2)", "if'(X < 1, 1, 2)"] {
+            let err = parse(expression).unwrap_err();
+            assert!(
+                err.to_string().contains("only user-defined functions"),
+                "{expression}: {err}"
+            );
+        }
+        let err = parse("2 + sqrt''(X)").unwrap_err();
+        assert_eq!(4..8, error_span(&err).unwrap().range());
+        assert!(!parse_tolerant("sin'(X)").diagnostics.is_empty());
+        assert!(parse("not'(X)").is_ok());
+    }
+
+    #[test]
+    fn divides_variables_named_like_derivatives() {
+        assert_eq!("(1d^(1)/1density^(1))", setup_basic("d/density"));
+        assert_eq!("(1d^(1)/1dt^(1))", setup_basic("d/dt"));
+        assert_eq!("(1d^(1)/1dX^(1))", setup_basic("d/dX"));
+        assert_eq!("((1d^(1)/1dt^(1))+1)", setup_basic("d/dt + 1"));
+    }
+
+    fn setup_config(expression: &str, config: &ParseConfig) -> String {
+        parse_with_config(expression, config).unwrap().to_string()
+    }
+
+    #[test]
+    fn can_parse_with_separators() {
+        let german = ParseConfig::new(',', ';', Some('.')).unwrap();
+        assert_eq!("3.5", setup_config("3,5", &german));
+        assert_eq!("max(1.5, 2)", setup_config("max(1,5; 2)", &german));
+        assert_eq!("(1000000.25*2)", setup_config("1.000.000,25 * 2", &german));
+        assert_eq!("[0.25, 1.5]", setup_config("[0,25;1,5]", &german));
+        assert_eq!("[0, 1)", setup_config("[0; 1)", &german));
+        assert_eq!("0.3333333333333333", setup_config("0,(3)", &german));
+        assert_eq!("1.5", setup_config("1.5", &german));
+
+        let finnish = ParseConfig::new(',', ';', Some(' ')).unwrap();
+        assert_eq!("(12000.5+1)", setup_config("12 000,5 + 1", &finnish));
+        assert!(parse_with_config("12 00", &finnish).is_err());
+
+        // `;` between statements isn't an argument separator
+        let statements = parse_statements_with_config("a := 2,5; f(a; 1)", &german).unwrap();
+        assert_eq!(2, statements.len());
+        assert!(
+            matches!(&statements[1], Statement::Expression(expr) if expr.to_string() == "f(1a^(1), 1)")
+        );
+
+        let default = ParseConfig::default();
+        assert_eq!("max(3, 5)", setup_config("max(3,5)", &default));
+        assert!(parse_with_config("max(3; 5)", &default).is_err());
+
+        let partial = parse_tolerant_with_config("max(1,5; 2) +", &german);
+        assert_eq!("(max(1.5, 2)+?)", partial.expr.to_string());
+        assert_eq!(1, partial.diagnostics.len());
+    }
+
+    #[test]
+    fn rejects_invalid_separators() {
+        assert!(ParseConfig::new(',', ',', None).is_err());
+        assert!(ParseConfig::new('.', ';', Some('.')).is_err());
+        assert!(ParseConfig::new(';', ',', None).is_err());
+        assert!(ParseConfig::new('.', ':', None).is_err());
+        assert!(ParseConfig::new('.', ',', Some('x')).is_err());
+        assert_eq!(
+            ParseConfig::default(),
+            ParseConfig::new('.', ',', None).unwrap()
+        );
+    }
+
+    #[test]
+    fn tolerant_parse_matches_strict_parse() {
+        for expression in [
+            "2+5*X",
+            "sin(30)^2",
+            "||-3| - 5|",
+            "[1, 2][1]",
+            "2(3+4)",
+            "1..n",
+            "d/dX f'(X)",
+        ] {
+            let partial = parse_tolerant(expression);
+            assert!(partial.diagnostics.is_empty(), "{expression}");
+            assert!(parse(expression).unwrap().same_shape(&partial.expr));
+        }
+    }
+
+    #[test]
+    fn tolerant_parse_recovers_from_errors() {
+        assert_eq!(
+            ("(2*(3+4))".to_string(), vec![(1, 2)]),
+            setup_tolerant("2(3+4")
+        );
+        assert_eq!(("(1+?)".to_string(), vec![(2, 2)]), setup_tolerant("1+"));
+        assert_eq!(
+            ("sin(?)".to_string(), vec![(0, 5)]),
+            setup_tolerant("sin()")
+        );
+        assert_eq!(("(1+2)".to_string(), vec![(3, 4)]), setup_tolerant("1+2)"));
+        assert_eq!(
+            ("[1, 2]".to_string(), vec![(0, 1)]),
+            setup_tolerant("[1, 2")
+        );
+        assert_eq!(
+            ("[1, ?, 2]".to_string(), vec![(3, 3)]),
+            setup_tolerant("[1,, 2]")
+        );
+        assert_eq!(
+            ("{1, 2}".to_string(), vec![(0, 1)]),
+            setup_tolerant("{1, 2")
+        );
+        assert_eq!(
+            ("[0, ?)".to_string(), vec![(4, 4)]),
+            setup_tolerant("[0, )")
+        );
+        assert_eq!(("?".to_string(), vec![(0, 0)]), setup_tolerant(""));
+        assert_eq!(
+            ("(2+(?*?))".to_string(), vec![(2, 3), (3, 3)]),
+            setup_tolerant("2+*")
+        );
+
+        assert_eq!(
+            ("(1+?)".to_string(), vec![(4, 13)]),
+            setup_tolerant("1 + (2, 3, 4)")
+        );
+        assert_eq!(
+            ("piecewise((1, ?), 2)".to_string(), vec![(14, 14)]),
+            setup_tolerant("piecewise((1, ), 2)")
+        );
+
+        assert_eq!(
+            ("not((1a^(1) or ?))".to_string(), vec![(4, 5), (9, 9)]),
+            setup_tolerant("not (a or ")
+        );
+
+        let (expr, spans) = setup_tolerant("sin(1+ * [2, (3");
+        assert_eq!("sin((1+(?*[2, 3])))", expr);
+        assert_eq!(vec![(3, 4), (7, 8), (9, 10), (13, 14)], spans);
+    }
 }
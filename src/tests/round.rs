@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use crate::math::round;
+    use crate::math::{nearly_equal, round};
 
     #[test]
     fn test_rounding_positive_number_to_int() {
@@ -37,4 +37,14 @@ mod test {
         assert_eq!(round(99999.999, 0), 100000.0);
         assert_eq!(round(9876543.210987654, 6), 9876543.210988);
     }
+
+    #[test]
+    fn test_nearly_equal_is_relative() {
+        assert!(nearly_equal(0.1 + 0.2, 0.3));
+        assert!(nearly_equal(1.602176634e-19 * 3.0, 4.806529902e-19));
+        assert!(!nearly_equal(6.626e-34, 1.6e-19));
+        assert!(!nearly_equal(1e-16, 2e-16));
+        assert!(nearly_equal(1e-16, 0.0));
+        assert!(nearly_equal(0.0, 0.0));
+    }
 }
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod test {
+    use crate::error::error_span;
+    use crate::numeric_evaluator::evaluate;
+    use crate::parser::{parse, parse_equation, parse_with_config, Expr, ParseConfig, Span};
+
+    fn parse_error_range(expression: &str) -> std::ops::Range<usize> {
+        error_span(&parse(expression).unwrap_err()).unwrap().range()
+    }
+
+    fn eval_error_range(expression: &str) -> std::ops::Range<usize> {
+        error_span(&evaluate(expression).unwrap_err())
+            .unwrap()
+            .range()
+    }
+
+    #[test]
+    fn keeps_spans_with_separators() {
+        let german = ParseConfig::new(',', ';', Some('.')).unwrap();
+        let expr = parse_with_config("max(1.000,5; 2)", &german).unwrap();
+        let Expr::Function { args, .. } = expr else {
+            panic!("expected a function call")
+        };
+        assert_eq!(4..11, args[0].span().range());
+        let err = parse_with_config("max(1,5;)", &german).unwrap_err();
+        assert_eq!(8..9, error_span(&err).unwrap().range());
+    }
+
+    #[test]
+    fn keeps_spans_on_nodes() {
+        let expr = parse("1 + 2*X").unwrap();
+        assert_eq!(0..7, expr.span().range());
+
+        let Expr::BinOp { lhs, rhs, .. } = expr else {
+            panic!("expected a binary operation")
+        };
+        assert_eq!(0..1, lhs.span().range());
+        assert_eq!(4..7, rhs.span().range());
+    }
+
+    #[test]
+    fn keeps_spans_on_functions_and_postfix() {
+        assert_eq!(0..9, parse("-max(1,2)").unwrap().span().range());
+        assert_eq!(0..8, parse("(1 + 2)!  ").unwrap().span().range());
+        assert_eq!(0..6, parse("|X-3|!").unwrap().span().range());
+    }
+
+    #[test]
+    fn keeps_spans_in_equations() {
+        let Expr::Relation { operands, .. } = parse_equation("1+1 = 4-2").unwrap() else {
+            panic!("expected an equation")
+        };
+        assert_eq!(0..3, operands[0].span().range());
+        assert_eq!(6..9, operands[1].span().range());
+    }
+
+    #[test]
+    fn spans_do_not_affect_equality() {
+        let expr = parse("1+2").unwrap();
+        assert!(expr.same_shape(&parse("  1 + 2").unwrap()));
+        assert_ne!(expr, parse("  1 + 2").unwrap());
+    }
+
+    #[test]
+    fn reports_syntax_error_positions() {
+        assert_eq!(4..5, parse_error_range("2 + * 3"));
+        assert_eq!(6..6, parse_error_range("(1 + 2"));
+        assert_eq!(0..3, parse_error_range("sin + 1"));
+        assert_eq!(4..21, parse_error_range("1 + 0.(3)e99999999999"));
+        assert_eq!(
+            4..5,
+            error_span(&parse_equation("1=2 =3").unwrap_err())
+                .unwrap()
+                .range()
+        );
+    }
+
+    #[test]
+    fn reports_evaluator_error_positions() {
+        assert_eq!(4..5, eval_error_range("1 + x"));
+        assert_eq!(2..11, eval_error_range("2*foo(1, 2)"));
+        assert_eq!(0..9, eval_error_range("sin(1, 2)"));
+    }
+
+    #[test]
+    fn converts_to_char_ranges() {
+        assert_eq!(2..3, Span::new(4, 5).char_range("ää+1"));
+    }
+
+    #[test]
+    fn reports_relation_positions_in_equations() {
+        let err = parse_equation("1 < X = 3").unwrap_err();
+        assert_eq!(2..3, error_span(&err).unwrap().range());
+        let err = parse_equation("X = 1 >= 0").unwrap_err();
+        assert_eq!(6..8, error_span(&err).unwrap().range());
+    }
+}
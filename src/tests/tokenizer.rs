@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod test {
+    use crate::parser::{
+        parse_statements, tokenize, tokenize_with_config, ParseConfig, TokenKind,
+    };
+
+    use TokenKind::*;
+
+    /// The kind and text of every token.
+    fn setup_tokens(expression: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(expression)
+            .tokens
+            .into_iter()
+            .map(|token| (token.kind, &expression[token.span.range()]))
+            .collect()
+    }
+
+    fn setup_brackets(expression: &str) -> Vec<(usize, usize)> {
+        tokenize(expression)
+            .brackets
+            .into_iter()
+            .map(|(open, close)| (open.start, close.start))
+            .collect()
+    }
+
+    #[test]
+    fn can_tokenize_expressions() {
+        assert_eq!(
+            vec![
+                (Number, "2"),
+                (Variable, "X"),
+                (Operator, "^"),
+                (Number, "2"),
+                (Operator, "+"),
+                (Function, "sin"),
+                (Paren, "("),
+                (Constant, "pi"),
+                (Operator, "/"),
+                (Number, "2"),
+                (Paren, ")"),
+            ],
+            setup_tokens("2X^2 + sin(pi / 2)")
+        );
+        assert_eq!(
+            vec![
+                (Function, "f"),
+                (Paren, "("),
+                (Number, "1"),
+                (Operator, ","),
+                (Paren, "["),
+                (Variable, "a"),
+                (Paren, "]"),
+                (Paren, ")"),
+                (Operator, "*"),
+                (Operator, "|"),
+                (Operator, "-"),
+                (Variable, "b"),
+                (Operator, "|"),
+                (Number, "²"),
+            ],
+            setup_tokens("f(1, [a]) * |-b|²")
+        );
+        assert_eq!(
+            vec![
+                (Number, "3"),
+                (Variable, "X"),
+                (Operator, "^"),
+                (Paren, "("),
+                (Number, "-1"),
+                (Operator, "/"),
+                (Number, "2"),
+                (Paren, ")"),
+                (Variable, "Y"),
+            ],
+            setup_tokens("3X^(-1/2)Y")
+        );
+        assert_eq!(
+            vec![
+                (Operator, "d/dX"),
+                (Function, "f"),
+                (Operator, "''"),
+                (Paren, "("),
+                (Variable, "X"),
+                (Paren, ")"),
+            ],
+            setup_tokens("d/dX f''(X)")
+        );
+        assert_eq!(vec![(Function, "sqrt")], setup_tokens("sqrt"));
+        assert!(setup_tokens("").is_empty());
+    }
+
+    #[test]
+    fn can_tokenize_logic() {
+        assert_eq!(
+            vec![
+                (Operator, "not"),
+                (Paren, "("),
+                (Constant, "true"),
+                (Operator, "and"),
+                (Variable, "p"),
+                (Paren, ")"),
+                (Operator, "or"),
+                (Constant, "false"),
+            ],
+            setup_tokens("not (true and p) or false")
+        );
+    }
+
+    #[test]
+    fn can_tokenize_statements() {
+        assert_eq!(
+            vec![
+                (Function, "f"),
+                (Paren, "("),
+                (Variable, "X"),
+                (Paren, ")"),
+                (Operator, ":="),
+                (Variable, "X"),
+                (Operator, "^"),
+                (Number, "2"),
+            ],
+            setup_tokens("f(X) := X^2")
+        );
+        assert_eq!(
+            vec![
+                (Variable, "a"),
+                (Operator, ":="),
+                (Number, "3"),
+                (Operator, ";"),
+                (Variable, "b"),
+                (Variable, "c"),
+            ],
+            setup_tokens("a := 3; b\nc")
+        );
+        for expression in [
+            "a := 3; b",
+            "f(X) := X^2; f(2)",
+            "\na := 1\n\nb := a + 1;\n",
+            "g(X, Y) := X * Y\ng(2, 3)",
+            "d/dX (X^2); b := |a|",
+        ] {
+            assert!(parse_statements(expression).is_ok(), "{expression}");
+            assert!(
+                setup_tokens(expression)
+                    .iter()
+                    .all(|(kind, _)| *kind != Error),
+                "{expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn marks_errors() {
+        assert_eq!(
+            vec![(Number, "1"), (Operator, "+"), (Error, "@"), (Number, "2")],
+            setup_tokens("1 +@ 2")
+        );
+        assert_eq!(
+            vec![(Number, "1"), (Error, ")"), (Operator, ";")],
+            setup_tokens("1);")
+        );
+        assert_eq!(
+            vec![(Variable, "a"), (Error, ":+")],
+            setup_tokens("a :+")
+        );
+        assert_eq!(
+            vec![(Error, "("), (Number, "1"), (Operator, "+")],
+            setup_tokens("(1+")
+        );
+    }
+
+    #[test]
+    fn can_tokenize_with_separators() {
+        let german = ParseConfig::new(',', ';', Some('.')).unwrap();
+        let expression = "max(1.000,5; 2)";
+        let tokens: Vec<_> = tokenize_with_config(expression, &german)
+            .tokens
+            .into_iter()
+            .map(|token| (token.kind, &expression[token.span.range()]))
+            .collect();
+        assert_eq!(
+            vec![
+                (Function, "max"),
+                (Paren, "("),
+                (Number, "1.000,5"),
+                (Operator, ";"),
+                (Number, "2"),
+                (Paren, ")")
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn can_pair_brackets() {
+        assert_eq!(
+            vec![(4, 6), (3, 7), (1, 9), (0, 10)],
+            setup_brackets("((2[(X)] ))")
+        );
+        assert_eq!(vec![(1, 3)], setup_brackets("([1]"));
+        assert!(setup_brackets("1)").is_empty());
+        assert_eq!(vec![(0, 5), (11, 13)], setup_brackets("[0, 1) ∪ {2}"));
+        assert_eq!(vec![(0, 5)], setup_brackets("[0, 1]]"));
+    }
+}